@@ -0,0 +1,151 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::hash_map::DefaultHasher;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// On-disk cache for the results of slow modules, stored as one JSON file per
+/// module under `$XDG_CACHE_HOME/hxfetch`.
+///
+/// Probes of another root (`--sysroot`) are kept in a subdirectory named after
+/// a hash of that root, so they never mix with the running system's entries.
+///
+/// An entry is reused while it is younger than the module's TTL and none of the
+/// module's input files (package databases and the like) changed their mtime.
+pub struct ProbeCache {
+    dir: Option<PathBuf>,
+    refresh: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CacheEntry {
+    /// Seconds since the Unix epoch when the value was collected
+    created: u64,
+    fingerprint: Vec<InputStamp>,
+    value: Value,
+}
+
+/// Modification time of one input file, `None` if it didn't exist
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct InputStamp {
+    path: PathBuf,
+    modified: Option<u128>,
+}
+
+impl ProbeCache {
+    /// Cache in the user's cache directory. With `refresh` set, stored entries
+    /// are ignored but fresh results are still written back.
+    pub fn new(refresh: bool) -> Self {
+        Self {
+            dir: dirs::cache_dir().map(|dir| dir.join("hxfetch")),
+            refresh,
+        }
+    }
+
+    /// Cache stored in `dir` instead of the user's cache directory
+    #[cfg(test)]
+    pub fn in_dir(dir: impl Into<PathBuf>, refresh: bool) -> Self {
+        Self {
+            dir: Some(dir.into()),
+            refresh,
+        }
+    }
+
+    /// A cache that never stores or returns anything
+    #[cfg(test)]
+    pub fn disabled() -> Self {
+        Self {
+            dir: None,
+            refresh: true,
+        }
+    }
+
+    /// Keeps the entries for the system mounted at `root` apart from those of
+    /// the running system
+    pub fn for_root(mut self, root: &Path) -> Self {
+        let root = root.canonicalize().unwrap_or_else(|_| root.to_path_buf());
+        if root != Path::new("/") {
+            let mut hasher = DefaultHasher::new();
+            root.hash(&mut hasher);
+            self.dir = self
+                .dir
+                .map(|dir| dir.join(format!("root-{:016x}", hasher.finish())));
+        }
+        self
+    }
+
+    /// Cached value for `module`, if it is still valid
+    pub fn load(&self, module: &str, ttl: Duration, inputs: &[PathBuf]) -> Option<Value> {
+        if self.refresh {
+            return None;
+        }
+
+        let contents = fs::read_to_string(self.entry_path(module)?).ok()?;
+        let entry: CacheEntry = serde_json::from_str(&contents).ok()?;
+
+        let age = Self::now().saturating_sub(entry.created);
+        if age > ttl.as_secs() || entry.fingerprint != Self::fingerprint(inputs) {
+            return None;
+        }
+
+        Some(entry.value)
+    }
+
+    pub fn store(&self, module: &str, inputs: &[PathBuf], value: &Value) {
+        let Some(path) = self.entry_path(module) else {
+            return;
+        };
+
+        let entry = CacheEntry {
+            created: Self::now(),
+            fingerprint: Self::fingerprint(inputs),
+            value: value.clone(),
+        };
+
+        // Errors are ignored, a missing cache only costs time
+        if let Some(parent) = path.parent() {
+            let _ = fs::create_dir_all(parent);
+        }
+        if let Ok(contents) = serde_json::to_string(&entry) {
+            // Write to a temporary file first so concurrent runs never see a
+            // half-written entry
+            let tmp = path.with_extension("json.tmp");
+            if fs::write(&tmp, contents).is_ok() {
+                let _ = fs::rename(&tmp, &path);
+            }
+        }
+    }
+
+    fn entry_path(&self, module: &str) -> Option<PathBuf> {
+        self.dir
+            .as_ref()
+            .map(|dir| dir.join(format!("{}.json", module)))
+    }
+
+    fn fingerprint(inputs: &[PathBuf]) -> Vec<InputStamp> {
+        inputs
+            .iter()
+            .map(|path| InputStamp {
+                path: path.clone(),
+                modified: Self::modified(path),
+            })
+            .collect()
+    }
+
+    fn modified(path: &Path) -> Option<u128> {
+        fs::metadata(path)
+            .and_then(|metadata| metadata.modified())
+            .ok()
+            .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+            .map(|duration| duration.as_nanos())
+    }
+
+    fn now() -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or(0)
+    }
+}
@@ -0,0 +1,520 @@
+use crate::terminal;
+use serde::{Deserialize, Serialize};
+use std::cell::Cell;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+thread_local! {
+    /// Whether `Painted` and `prepare_escapes` emit escapes, see `set_enabled`
+    static ENABLED: Cell<bool> = const { Cell::new(true) };
+}
+
+/// The 16 standard terminal colors as xterm draws them, used to pick the
+/// nearest one on terminals without 256-color support
+const BASIC_PALETTE: [(u8, u8, u8); 16] = [
+    (0, 0, 0),
+    (205, 0, 0),
+    (0, 205, 0),
+    (205, 205, 0),
+    (0, 0, 238),
+    (205, 0, 205),
+    (0, 205, 205),
+    (229, 229, 229),
+    (127, 127, 127),
+    (255, 0, 0),
+    (0, 255, 0),
+    (255, 255, 0),
+    (92, 92, 255),
+    (255, 0, 255),
+    (0, 255, 255),
+    (255, 255, 255),
+];
+
+/// Channel values of the 6x6x6 color cube in the 256-color palette
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+/// A color from the config: one of the eight named ANSI colors, an index into
+/// the 256-color palette, or a 24-bit RGB value.
+///
+/// Accepted spellings are `"cyan"`, `"#89b4fa"`, `"#abc"`, `"rgb(137, 180, 250)"`
+/// and plain numbers from 0 to 255.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "ColorValue", into = "ColorValue")]
+pub enum ColorChoice {
+    Red,
+    Green,
+    Blue,
+    Yellow,
+    Magenta,
+    Cyan,
+    White,
+    Black,
+    Ansi256(u8),
+    Rgb(u8, u8, u8),
+}
+
+/// How colors are written to the config file
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum ColorValue {
+    Index(u8),
+    Text(String),
+}
+
+impl TryFrom<ColorValue> for ColorChoice {
+    type Error = String;
+
+    fn try_from(value: ColorValue) -> Result<Self, Self::Error> {
+        match value {
+            ColorValue::Index(index) => Ok(ColorChoice::Ansi256(index)),
+            ColorValue::Text(text) => text.parse(),
+        }
+    }
+}
+
+impl From<ColorChoice> for ColorValue {
+    fn from(color: ColorChoice) -> Self {
+        match color {
+            ColorChoice::Ansi256(index) => ColorValue::Index(index),
+            other => ColorValue::Text(other.to_string()),
+        }
+    }
+}
+
+impl FromStr for ColorChoice {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let text = text.trim();
+        let lower = text.to_ascii_lowercase();
+
+        let named = match lower.as_str() {
+            "red" => Some(ColorChoice::Red),
+            "green" => Some(ColorChoice::Green),
+            "blue" => Some(ColorChoice::Blue),
+            "yellow" => Some(ColorChoice::Yellow),
+            "magenta" => Some(ColorChoice::Magenta),
+            "cyan" => Some(ColorChoice::Cyan),
+            "white" => Some(ColorChoice::White),
+            "black" => Some(ColorChoice::Black),
+            _ => None,
+        };
+
+        let parsed = named
+            .or_else(|| Self::parse_hex(&lower))
+            .or_else(|| Self::parse_rgb(&lower))
+            .or_else(|| lower.parse().ok().map(ColorChoice::Ansi256));
+
+        parsed.ok_or_else(|| {
+            format!(
+                "invalid color `{}`, expected a name such as \"cyan\", a hex value such as \"#89b4fa\", \"rgb(137, 180, 250)\" or a 256-color index",
+                text
+            )
+        })
+    }
+}
+
+impl fmt::Display for ColorChoice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ColorChoice::Red => write!(f, "Red"),
+            ColorChoice::Green => write!(f, "Green"),
+            ColorChoice::Blue => write!(f, "Blue"),
+            ColorChoice::Yellow => write!(f, "Yellow"),
+            ColorChoice::Magenta => write!(f, "Magenta"),
+            ColorChoice::Cyan => write!(f, "Cyan"),
+            ColorChoice::White => write!(f, "White"),
+            ColorChoice::Black => write!(f, "Black"),
+            ColorChoice::Ansi256(index) => write!(f, "{}", index),
+            ColorChoice::Rgb(r, g, b) => write!(f, "#{:02x}{:02x}{:02x}", r, g, b),
+        }
+    }
+}
+
+impl ColorChoice {
+    fn parse_hex(text: &str) -> Option<Self> {
+        let hex = text.strip_prefix('#')?;
+        if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return None;
+        }
+
+        let channel = |digits: &str| u8::from_str_radix(digits, 16).ok();
+        match hex.len() {
+            6 => Some(ColorChoice::Rgb(
+                channel(&hex[0..2])?,
+                channel(&hex[2..4])?,
+                channel(&hex[4..6])?,
+            )),
+            // #abc is short for #aabbcc
+            3 => {
+                let short = |i: usize| channel(&hex[i..i + 1]).map(|v| v * 17);
+                Some(ColorChoice::Rgb(short(0)?, short(1)?, short(2)?))
+            }
+            _ => None,
+        }
+    }
+
+    fn parse_rgb(text: &str) -> Option<Self> {
+        let inner = text.strip_prefix("rgb(")?.strip_suffix(')')?;
+        let channels: Vec<u8> = inner
+            .split(',')
+            .map(|part| part.trim().parse().ok())
+            .collect::<Option<_>>()?;
+
+        match channels.as_slice() {
+            [r, g, b] => Some(ColorChoice::Rgb(*r, *g, *b)),
+            _ => None,
+        }
+    }
+
+    /// Index of the named colors in the 16-color palette
+    fn basic_index(self) -> Option<u8> {
+        match self {
+            ColorChoice::Black => Some(0),
+            ColorChoice::Red => Some(1),
+            ColorChoice::Green => Some(2),
+            ColorChoice::Yellow => Some(3),
+            ColorChoice::Blue => Some(4),
+            ColorChoice::Magenta => Some(5),
+            ColorChoice::Cyan => Some(6),
+            ColorChoice::White => Some(7),
+            ColorChoice::Ansi256(_) | ColorChoice::Rgb(..) => None,
+        }
+    }
+
+    /// SGR parameters that select this color as the foreground on a terminal
+    /// with the given depth, downgrading it if the terminal can't show it
+    pub fn fg_params(self, depth: ColorDepth) -> String {
+        if let Some(index) = self.basic_index() {
+            return basic_params(index);
+        }
+
+        match (self, depth) {
+            (ColorChoice::Rgb(r, g, b), ColorDepth::TrueColor) => {
+                format!("38;2;{};{};{}", r, g, b)
+            }
+            (ColorChoice::Rgb(r, g, b), ColorDepth::Ansi256) => {
+                format!("38;5;{}", nearest_ansi256(r, g, b))
+            }
+            (ColorChoice::Ansi256(index), ColorDepth::TrueColor | ColorDepth::Ansi256) => {
+                format!("38;5;{}", index)
+            }
+            (ColorChoice::Ansi256(index), ColorDepth::Basic) if index < 16 => basic_params(index),
+            (ColorChoice::Ansi256(index), ColorDepth::Basic) => {
+                let (r, g, b) = ansi256_to_rgb(index);
+                basic_params(nearest_basic(r, g, b))
+            }
+            (ColorChoice::Rgb(r, g, b), ColorDepth::Basic) => basic_params(nearest_basic(r, g, b)),
+            _ => String::new(),
+        }
+    }
+
+    /// Starts styling `text` with this color
+    pub fn paint(self, text: impl Into<String>) -> Painted {
+        Painted {
+            text: text.into(),
+            color: self,
+            bold: false,
+            italic: false,
+        }
+    }
+}
+
+/// Text with a color and attributes, rendered to escapes by `Display`
+pub struct Painted {
+    text: String,
+    color: ColorChoice,
+    bold: bool,
+    italic: bool,
+}
+
+impl Painted {
+    pub fn bold(mut self) -> Self {
+        self.bold = true;
+        self
+    }
+
+    pub fn italic(mut self) -> Self {
+        self.italic = true;
+        self
+    }
+}
+
+impl fmt::Display for Painted {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.text.is_empty() {
+            return Ok(());
+        }
+        if !enabled() {
+            return f.write_str(&self.text);
+        }
+
+        let mut params = Vec::new();
+        if self.bold {
+            params.push("1".to_string());
+        }
+        if self.italic {
+            params.push("3".to_string());
+        }
+        params.push(self.color.fg_params(ColorDepth::current()));
+
+        write!(f, "\x1b[{}m{}\x1b[0m", params.join(";"), self.text)
+    }
+}
+
+/// When to color the output, chosen with `--color`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColorMode {
+    Auto,
+    Always,
+    Never,
+}
+
+impl FromStr for ColorMode {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        match text {
+            "auto" => Ok(ColorMode::Auto),
+            "always" => Ok(ColorMode::Always),
+            "never" => Ok(ColorMode::Never),
+            _ => Err(format!(
+                "invalid color mode `{}`, expected auto, always or never",
+                text
+            )),
+        }
+    }
+}
+
+impl ColorMode {
+    /// Whether to emit colors. In auto mode a non-empty `NO_COLOR` turns
+    /// colors off, and a `CLICOLOR_FORCE` other than "0" turns them on even
+    /// when stdout is not a terminal.
+    pub fn resolve(
+        self,
+        no_color: Option<&str>,
+        clicolor_force: Option<&str>,
+        is_tty: bool,
+    ) -> bool {
+        match self {
+            ColorMode::Always => true,
+            ColorMode::Never => false,
+            ColorMode::Auto => {
+                if no_color.is_some_and(|value| !value.is_empty()) {
+                    false
+                } else if clicolor_force.is_some_and(|value| !value.is_empty() && value != "0") {
+                    true
+                } else {
+                    is_tty
+                }
+            }
+        }
+    }
+}
+
+/// Turns color output on or off for the rest of the run. The setting is kept
+/// per thread; the output is written from the main thread.
+pub fn set_enabled(enabled: bool) {
+    ENABLED.with(|cell| cell.set(enabled));
+}
+
+pub fn enabled() -> bool {
+    ENABLED.with(Cell::get)
+}
+
+/// How many colors the terminal can show
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColorDepth {
+    /// The 16 standard colors
+    Basic,
+    Ansi256,
+    TrueColor,
+}
+
+impl ColorDepth {
+    /// Depth of the terminal we are running in, detected once
+    pub fn current() -> Self {
+        static DEPTH: OnceLock<ColorDepth> = OnceLock::new();
+        *DEPTH.get_or_init(|| {
+            Self::from_env(
+                std::env::var("COLORTERM").ok().as_deref(),
+                std::env::var("TERM").ok().as_deref(),
+            )
+        })
+    }
+
+    /// Depth advertised by the `COLORTERM` and `TERM` variables
+    pub fn from_env(colorterm: Option<&str>, term: Option<&str>) -> Self {
+        if matches!(colorterm, Some("truecolor" | "24bit")) {
+            return ColorDepth::TrueColor;
+        }
+
+        match term {
+            Some(term) if term.ends_with("-direct") || term.contains("truecolor") => {
+                ColorDepth::TrueColor
+            }
+            Some(term) if term.contains("256color") => ColorDepth::Ansi256,
+            _ => ColorDepth::Basic,
+        }
+    }
+}
+
+/// Adapts the escapes embedded in `text`, such as the ones in generated logos,
+/// to the output: removed when colors are off, otherwise converted to the
+/// terminal's color depth
+pub fn prepare_escapes(text: &str) -> String {
+    if enabled() {
+        quantize_escapes(text, ColorDepth::current())
+    } else {
+        terminal::strip_escapes(text)
+    }
+}
+
+/// Rewrites the 24-bit and 256-color escapes embedded in `text`, such as the
+/// ones in generated logos, to what a terminal of `depth` can show
+pub fn quantize_escapes(text: &str, depth: ColorDepth) -> String {
+    if depth == ColorDepth::TrueColor || !text.contains('\x1b') {
+        return text.to_string();
+    }
+
+    let mut output = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find("\x1b[") {
+        output.push_str(&rest[..start]);
+        let sequence = &rest[start + 2..];
+
+        // Only SGR sequences (ending in 'm') carry colors
+        let end = sequence.find(|c: char| !(c.is_ascii_digit() || c == ';'));
+        match end {
+            Some(end) if sequence[end..].starts_with('m') => {
+                output.push_str("\x1b[");
+                output.push_str(&quantize_params(&sequence[..end], depth));
+                output.push('m');
+                rest = &sequence[end + 1..];
+            }
+            _ => {
+                output.push_str("\x1b[");
+                rest = sequence;
+            }
+        }
+    }
+
+    output.push_str(rest);
+    output
+}
+
+fn quantize_params(params: &str, depth: ColorDepth) -> String {
+    let values: Vec<&str> = params.split(';').collect();
+    let mut output: Vec<String> = Vec::new();
+    let mut i = 0;
+
+    while i < values.len() {
+        let background = match values[i] {
+            "38" => false,
+            "48" => true,
+            value => {
+                output.push(value.to_string());
+                i += 1;
+                continue;
+            }
+        };
+
+        let number = |offset: usize| values.get(i + offset).and_then(|v| v.parse::<u8>().ok());
+        let color = match values.get(i + 1) {
+            Some(&"2") => match (number(2), number(3), number(4)) {
+                (Some(r), Some(g), Some(b)) => Some((ColorChoice::Rgb(r, g, b), 5)),
+                _ => None,
+            },
+            Some(&"5") => number(2).map(|index| (ColorChoice::Ansi256(index), 3)),
+            _ => None,
+        };
+
+        match color {
+            Some((color, consumed)) => {
+                let params = color.fg_params(depth);
+                output.push(if background {
+                    to_background(&params)
+                } else {
+                    params
+                });
+                i += consumed;
+            }
+            None => {
+                output.push(values[i].to_string());
+                i += 1;
+            }
+        }
+    }
+
+    output.join(";")
+}
+
+/// Turns foreground SGR parameters into the matching background ones
+fn to_background(params: &str) -> String {
+    if let Some(rest) = params.strip_prefix("38;") {
+        return format!("48;{}", rest);
+    }
+    match params.parse::<u8>() {
+        Ok(code @ 30..=37) | Ok(code @ 90..=97) => (code + 10).to_string(),
+        _ => params.to_string(),
+    }
+}
+
+/// Foreground SGR code of a color in the 16-color palette
+fn basic_params(index: u8) -> String {
+    if index < 8 {
+        (30 + index).to_string()
+    } else {
+        (90 + index - 8).to_string()
+    }
+}
+
+fn distance(a: (u8, u8, u8), b: (u8, u8, u8)) -> u32 {
+    let d = |x: u8, y: u8| (x as i32 - y as i32).pow(2) as u32;
+    d(a.0, b.0) + d(a.1, b.1) + d(a.2, b.2)
+}
+
+fn nearest_basic(r: u8, g: u8, b: u8) -> u8 {
+    (0..16u8)
+        .min_by_key(|&index| distance((r, g, b), BASIC_PALETTE[index as usize]))
+        .unwrap_or(7)
+}
+
+/// Nearest entry of the color cube or the grayscale ramp
+fn nearest_ansi256(r: u8, g: u8, b: u8) -> u8 {
+    let level = |v: u8| {
+        (0..6)
+            .min_by_key(|&i| (CUBE_LEVELS[i] as i32 - v as i32).abs())
+            .unwrap_or(0) as u8
+    };
+    let cube = 16 + 36 * level(r) + 6 * level(g) + level(b);
+
+    let average = (r as u32 + g as u32 + b as u32) / 3;
+    let gray = 232 + ((average.saturating_sub(3)) / 10).min(23) as u8;
+
+    if distance((r, g, b), ansi256_to_rgb(gray)) < distance((r, g, b), ansi256_to_rgb(cube)) {
+        gray
+    } else {
+        cube
+    }
+}
+
+fn ansi256_to_rgb(index: u8) -> (u8, u8, u8) {
+    match index {
+        0..=15 => BASIC_PALETTE[index as usize],
+        16..=231 => {
+            let index = index - 16;
+            (
+                CUBE_LEVELS[(index / 36) as usize],
+                CUBE_LEVELS[(index / 6 % 6) as usize],
+                CUBE_LEVELS[(index % 6) as usize],
+            )
+        }
+        _ => {
+            let level = 8 + 10 * (index - 232);
+            (level, level, level)
+        }
+    }
+}
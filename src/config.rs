@@ -1,122 +1,531 @@
-use crate::display::ColorChoice;
+use crate::color::ColorChoice;
+use crate::layout::{LayoutBlock, Template};
+use crate::logo::{LogoAlign, LogoPosition};
+use crate::migrate::{ConfigMigrator, CONFIG_VERSION};
+use crate::probe::{ProbeContext, SystemRunner};
+use crate::sysinfo::{SystemInfo, TemperatureUnit};
+use crate::terminal::Overflow;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Config {
-    pub show_os: bool,
-    pub show_kernel: bool,
-    pub show_uptime: bool,
-    pub show_cpu: bool,
-    pub show_memory: bool,
-    pub show_storage: bool,
-    pub show_shell: bool,
-    pub show_desktop: bool,
-    pub show_terminal: bool,
-    pub show_colors: bool,
-
-    // Security options
-    pub show_security: bool,
-    pub show_firewall: bool,
-    pub show_selinux: bool,
-    pub show_apparmor: bool,
-    pub show_updates: bool,
-    pub show_ssh: bool,
-    pub show_fail2ban: bool,
-    pub show_encryption: bool,
-    pub show_secure_boot: bool,
-
-    // Advanced metrics
-    pub show_gpu: bool,
-    pub show_temperature: bool,
-    pub show_network: bool,
-    pub show_packages: bool,
+    /// Schema version, used to migrate older config files
+    pub version: u32,
+
+    /// Info modules to show, in display order. See `ModuleRegistry` for the
+    /// available names.
+    pub modules: Vec<String>,
 
     // Privacy settings
     pub redact_cpu_model: bool,
 
+    // Header settings
+    /// Show the fully qualified hostname from `/etc/hosts` in the header
+    pub fqdn: bool,
+
     // Logo settings
     pub custom_logo_path: Option<String>,
     pub logo_width: Option<u32>,
     pub logo_height: Option<u32>,
+    pub logo_position: LogoPosition,
+    /// Blank columns to the left of the logo
+    pub logo_padding: usize,
+    /// Columns between the logo and the info lines when they are side by side
+    pub logo_gap: usize,
+    pub logo_align: LogoAlign,
+
+    // Terminal width settings
+    /// Below this many columns a logo on the left or right is drawn above the
+    /// info lines instead
+    pub stack_below_columns: usize,
+    /// Below this many columns the logo is left out
+    pub hide_logo_below_columns: usize,
+    /// Whether info lines wider than the terminal are truncated or wrapped
+    pub overflow: Overflow,
+
+    // Storage settings
+    /// Mount point globs to show; empty shows all real filesystems
+    pub storage_include: Vec<String>,
+    pub storage_exclude: Vec<String>,
+    /// Filesystem type globs to show; empty shows all
+    pub storage_fs_include: Vec<String>,
+    pub storage_fs_exclude: Vec<String>,
+
+    // Network settings
+    /// Interface name globs listed by the `interfaces` module; empty lists all
+    pub network_include: Vec<String>,
+    pub network_exclude: Vec<String>,
+    /// Also list interfaces whose link is down
+    pub network_show_down: bool,
+
+    // Sensor settings
+    pub temperature_unit: TemperatureUnit,
+    /// `chip/label` globs of the temperatures to show, such as
+    /// `coretemp/Package*`; empty shows the first sensor of every chip
+    pub temperature_sensors: Vec<String>,
+    /// `chip/label` globs of the fans to show; empty shows all
+    pub fan_sensors: Vec<String>,
+
+    // Usage bar settings
+    /// Draw a bar after the memory, swap and disk usage
+    pub usage_bars: bool,
+    /// Bar width in characters, without the brackets
+    pub bar_width: usize,
+    pub bar_fill: String,
+    pub bar_empty: String,
+    /// Usage percentages from which the bar turns yellow and red
+    pub bar_warn_percent: u32,
+    pub bar_crit_percent: u32,
+
+    // Color settings
+    /// Built-in preset or the name of a file in `themes/`
+    pub theme: Option<String>,
+    /// Colors that override the theme's
+    pub logo_color: Option<ColorChoice>,
+    pub accent_color: Option<ColorChoice>,
+    pub label_color: Option<ColorChoice>,
+    pub value_color: Option<ColorChoice>,
+    pub separator_color: Option<ColorChoice>,
 
-    pub logo_color: ColorChoice,
-    pub accent_color: ColorChoice,
-    pub label_color: ColorChoice,
-    pub value_color: ColorChoice,
-    pub separator_color: ColorChoice,
+    // Probe settings
+    /// Maximum time in milliseconds an external command may run before it is
+    /// killed and its module falls back to "Unknown"
+    pub probe_timeout_ms: u64,
+    /// Per-module overrides for `probe_timeout_ms`, keyed by module name
+    pub probe_timeouts: HashMap<String, u64>,
+    /// Seconds a module's result is reused from the on-disk cache, keyed by
+    /// module name. Modules without an entry are never cached.
+    pub cache_ttl: HashMap<String, u64>,
+
+    /// Template for the info lines. When empty the built-in layout is used;
+    /// otherwise only modules referenced here are collected.
+    pub layout: Vec<LayoutBlock>,
 }
 
 impl Default for Config {
     fn default() -> Self {
         Self {
-            show_os: true,
-            show_kernel: true,
-            show_uptime: true,
-            show_cpu: true,
-            show_memory: true,
-            show_storage: true,
-            show_shell: true,
-            show_desktop: true,
-            show_terminal: true,
-            show_colors: true,
-
-            // Security defaults
-            show_security: true,
-            show_firewall: true,
-            show_selinux: true,
-            show_apparmor: true,
-            show_updates: true,
-            show_ssh: true,
-            show_fail2ban: true,
-            show_encryption: true,
-            show_secure_boot: true,
-
-            // Advanced metrics defaults
-            show_gpu: true,
-            show_temperature: true,
-            show_network: true,
-            show_packages: true,
+            version: CONFIG_VERSION,
+            modules: Self::default_modules(),
 
             // Privacy defaults
             redact_cpu_model: false,
 
+            // Header defaults
+            fqdn: false,
+
             // Logo defaults
             custom_logo_path: None,
             logo_width: Some(30),
             logo_height: Some(20),
+            logo_position: LogoPosition::Left,
+            logo_padding: 0,
+            logo_gap: 9,
+            logo_align: LogoAlign::Top,
+            stack_below_columns: 70,
+            hide_logo_below_columns: 40,
+            overflow: Overflow::Truncate,
+            storage_include: Vec::new(),
+            storage_exclude: Vec::new(),
+            storage_fs_include: Vec::new(),
+            storage_fs_exclude: Vec::new(),
+            network_include: Vec::new(),
+            network_exclude: Vec::new(),
+            network_show_down: false,
+            temperature_unit: TemperatureUnit::Celsius,
+            temperature_sensors: Vec::new(),
+            fan_sensors: Vec::new(),
+            usage_bars: false,
+            bar_width: 10,
+            bar_fill: "█".to_string(),
+            bar_empty: "░".to_string(),
+            bar_warn_percent: 70,
+            bar_crit_percent: 90,
+
+            // Color defaults come from the theme
+            theme: None,
+            logo_color: None,
+            accent_color: None,
+            label_color: None,
+            value_color: None,
+            separator_color: None,
+
+            // Probe defaults
+            probe_timeout_ms: 2000,
+            probe_timeouts: HashMap::new(),
+            cache_ttl: HashMap::from([
+                ("packages".to_string(), 3600),
+                ("updates".to_string(), 3600),
+            ]),
 
-            logo_color: ColorChoice::Cyan,
-            accent_color: ColorChoice::Blue,
-            label_color: ColorChoice::Cyan,
-            value_color: ColorChoice::White,
-            separator_color: ColorChoice::Blue,
+            layout: Vec::new(),
         }
     }
 }
 
 impl Config {
-    pub fn load() -> Self {
+    pub fn default_modules() -> Vec<String> {
+        [
+            "os",
+            "kernel",
+            "uptime",
+            "cpu",
+            "memory",
+            "swap",
+            "storage",
+            "shell",
+            "desktop",
+            "terminal",
+            "gpu",
+            "temperature",
+            "network",
+            "packages",
+            "firewall",
+            "selinux",
+            "ssh",
+            "fail2ban",
+            "secure_boot",
+            "apparmor",
+            "updates",
+            "encryption",
+            "colors",
+        ]
+        .iter()
+        .map(|name| name.to_string())
+        .collect()
+    }
+
+    /// Timeout for the external commands run by the named module
+    pub fn probe_timeout(&self, module: &str) -> Duration {
+        let millis = self
+            .probe_timeouts
+            .get(module)
+            .copied()
+            .unwrap_or(self.probe_timeout_ms);
+        Duration::from_millis(millis)
+    }
+
+    /// How long the named module's result may be served from the cache
+    pub fn cache_ttl(&self, module: &str) -> Option<Duration> {
+        self.cache_ttl
+            .get(module)
+            .map(|secs| Duration::from_secs(*secs))
+    }
+
+    /// Names looked up by the layout templates, such as `os.name` or `gpu`
+    pub fn layout_fields(&self) -> Vec<String> {
+        self.layout
+            .iter()
+            .flat_map(|block| &block.lines)
+            .filter_map(|line| Template::parse(line).ok())
+            .flat_map(|template| template.fields().map(str::to_string).collect::<Vec<_>>())
+            .collect()
+    }
+
+    /// Overrides a single setting from the command line, e.g. `logo_color=Red`
+    /// or `probe_timeouts.gpu=500`. The value is read as TOML and falls back to
+    /// a plain string, so strings don't need quotes.
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        let value = format!("value = {}", value)
+            .parse::<toml::Table>()
+            .ok()
+            .and_then(|mut table| table.remove("value"))
+            .unwrap_or_else(|| toml::Value::String(value.to_string()));
+
+        let mut table = toml::Table::try_from(&*self).map_err(|e| e.to_string())?;
+        let mut parts = key.split('.').peekable();
+        let mut current = &mut table;
+        while let Some(part) = parts.next() {
+            if part.is_empty() {
+                return Err(format!("invalid key `{}`", key));
+            }
+            if parts.peek().is_none() {
+                current.insert(part.to_string(), value);
+                break;
+            }
+            current = current
+                .entry(part)
+                .or_insert_with(|| toml::Value::Table(toml::Table::new()))
+                .as_table_mut()
+                .ok_or_else(|| format!("`{}` is not a table", part))?;
+        }
+
+        let contents = toml::to_string(&table).map_err(|e| e.to_string())?;
+        let (config, warnings) = Self::parse(&contents).map_err(|e| e.message)?;
+        if let Some(warning) = warnings.into_iter().next() {
+            return Err(warning.message);
+        }
+
+        *self = config;
+        Ok(())
+    }
+
+    /// Adds a module to the end of the list unless it is already shown
+    pub fn show_module(&mut self, name: &str) {
+        if !self.modules.iter().any(|module| module == name) {
+            self.modules.push(name.to_string());
+        }
+    }
+
+    pub fn hide_module(&mut self, name: &str) {
+        self.modules.retain(|module| module != name);
+    }
+
+    /// Loads the user's config layers, creating the base config with the
+    /// defaults if it doesn't exist yet
+    pub fn load(profile: Option<&str>) -> Self {
         let config_path = Self::get_config_path();
 
-        if config_path.exists() {
-            if let Ok(contents) = fs::read_to_string(&config_path) {
-                if let Ok(config) = toml::from_str(&contents) {
-                    return config;
+        if !config_path.exists() {
+            // Create the base config file, profiles and host files still apply
+            let _ = Self::default().save();
+        }
+
+        Self::load_from(&config_path, profile)
+    }
+
+    /// Loads `config_path` and the layers next to it, see `layer_paths`. Each
+    /// layer only needs the keys it changes; they are merged key by key over
+    /// the defaults, later layers winning.
+    pub fn load_from(config_path: &Path, profile: Option<&str>) -> Self {
+        let mut merged = toml::Table::try_from(Self::default()).unwrap_or_default();
+
+        for (index, path) in Self::layer_paths(config_path, profile).iter().enumerate() {
+            if let Some(layer) = Self::load_layer(path, index == 0) {
+                Self::merge(&mut merged, layer);
+            }
+        }
+
+        toml::to_string(&merged)
+            .ok()
+            .and_then(|contents| Self::parse(&contents).ok())
+            .map(|(config, _)| config)
+            .unwrap_or_default()
+    }
+
+    /// Config files applied on top of each other: the base config, the
+    /// selected profile from `profiles/<name>.toml` and the machine's
+    /// `hosts/<hostname>.toml`, both looked up next to the base config.
+    /// The profile is always listed so a missing one gets reported; the host
+    /// file only if it exists.
+    pub fn layer_paths(config_path: &Path, profile: Option<&str>) -> Vec<PathBuf> {
+        let dir = config_path.parent().unwrap_or(Path::new("."));
+        let mut paths = vec![config_path.to_path_buf()];
+
+        if let Some(profile) = profile {
+            paths.push(dir.join("profiles").join(format!("{}.toml", profile)));
+        }
+        if let Some(hostname) = Self::hostname() {
+            let host_path = dir.join("hosts").join(format!("{}.toml", hostname));
+            if host_path.exists() {
+                paths.push(host_path);
+            }
+        }
+
+        paths
+    }
+
+    /// Reads one layer. An older base config is migrated in memory only, the
+    /// file is left for `hxfetch config migrate` to update. Profiles and host
+    /// files only hold the keys they change, so migrating them would fill in
+    /// defaults that override the base config; they are reported instead. A
+    /// file that can't be read or fails to parse is reported, left untouched
+    /// and skipped.
+    fn load_layer(config_path: &Path, base: bool) -> Option<toml::Table> {
+        let contents = match fs::read_to_string(config_path) {
+            Ok(contents) => contents,
+            Err(e) => {
+                eprintln!(
+                    "Warning: Failed to read config {}: {}",
+                    config_path.display(),
+                    e
+                );
+                return None;
+            }
+        };
+
+        let contents = match ConfigMigrator::migrate(&contents) {
+            // Only the version is behind, the file reads the same
+            Some(migration) if !migration.keys_changed => contents,
+            Some(migration) if !base => {
+                eprintln!(
+                    "Warning: {} uses keys from config version {}, which are ignored. Run `hxfetch --config {} config migrate` to convert them.",
+                    config_path.display(),
+                    migration.from,
+                    config_path.display()
+                );
+                contents
+            }
+            Some(migration) => {
+                eprintln!(
+                    "Note: {} uses config version {}, read as version {}. Run `hxfetch config migrate` to update the file.",
+                    config_path.display(),
+                    migration.from,
+                    migration.to
+                );
+                migration.migrated
+            }
+            None => contents,
+        };
+
+        match Self::parse(&contents) {
+            Ok((_, warnings)) => {
+                for warning in warnings {
+                    eprintln!("Warning: {}: {}", config_path.display(), warning);
                 }
+                contents.parse().ok()
+            }
+            Err(error) => {
+                eprintln!("Error: {}: {}", config_path.display(), error);
+                eprintln!(
+                    "Ignoring this file, it was not modified. Run `hxfetch --check-config` for details."
+                );
+                None
+            }
+        }
+    }
+
+    /// Merges `layer` into `base`. Tables are merged recursively, any other
+    /// value (including lists such as `modules`) replaces the one below.
+    pub fn merge(base: &mut toml::Table, layer: toml::Table) {
+        for (key, value) in layer {
+            match (base.get_mut(&key), value) {
+                (Some(toml::Value::Table(below)), toml::Value::Table(above)) => {
+                    Self::merge(below, above)
+                }
+                (_, value) => {
+                    base.insert(key, value);
+                }
+            }
+        }
+    }
+
+    /// Hostname of the running system, used to pick `hosts/<hostname>.toml`
+    fn hostname() -> Option<String> {
+        let ctx = ProbeContext::new(
+            Arc::new(SystemRunner),
+            "/",
+            Duration::from_millis(Self::default().probe_timeout_ms),
+        );
+        Some(SystemInfo::get_hostname(&ctx)).filter(|hostname| hostname != "Unknown")
+    }
+
+    /// Parses a config file. Keys that are missing fall back to their defaults;
+    /// keys that are not recognized are returned as warnings.
+    pub fn parse(contents: &str) -> Result<(Self, Vec<Diagnostic>), Diagnostic> {
+        let mut unknown_keys = Vec::new();
+        let deserializer = toml::Deserializer::new(contents);
+
+        let config: Self = serde_ignored::deserialize(deserializer, |path| {
+            unknown_keys.push(path.to_string());
+        })
+        .map_err(|e| Diagnostic::from_toml_error(contents, &e))?;
+
+        let mut warnings: Vec<Diagnostic> = unknown_keys
+            .into_iter()
+            .map(|key| Diagnostic {
+                position: Diagnostic::find_key(contents, &key),
+                message: format!("unknown key `{}`", key),
+            })
+            .collect();
+
+        for line in config.layout.iter().flat_map(|block| &block.lines) {
+            if let Err(e) = Template::parse(line) {
+                return Err(Diagnostic {
+                    position: Diagnostic::find_value(contents, line),
+                    message: format!("invalid layout line: {}", e),
+                });
             }
         }
 
-        // Return default config and create config file
-        let default_config = Self::default();
-        let _ = default_config.save();
-        default_config
+        if config.version > CONFIG_VERSION {
+            warnings.push(Diagnostic {
+                position: Diagnostic::find_key(contents, "version"),
+                message: format!(
+                    "config version {} is newer than the supported version {}",
+                    config.version, CONFIG_VERSION
+                ),
+            });
+        }
+
+        Ok((config, warnings))
+    }
+
+    /// Validates a config file without loading it, for `--check-config`.
+    /// Module names are checked against `known_modules`.
+    pub fn check(path: &Path, known_modules: &[&str]) -> ConfigReport {
+        let mut report = ConfigReport {
+            path: path.to_path_buf(),
+            errors: Vec::new(),
+            warnings: Vec::new(),
+        };
+
+        match fs::read_to_string(path) {
+            Ok(contents) => {
+                // Check what the next run would load. Positions in a migrated
+                // file don't match the file on disk, so they are dropped.
+                let (contents, migrated) = match ConfigMigrator::migrate(&contents) {
+                    Some(migration) => {
+                        report.warnings.push(Diagnostic {
+                            position: None,
+                            message: format!(
+                                "config version {} is read as version {}; run `hxfetch config migrate` to update the file (preview with `--dry-run`)",
+                                migration.from, migration.to
+                            ),
+                        });
+                        if migration.keys_changed {
+                            (migration.migrated, true)
+                        } else {
+                            (contents, false)
+                        }
+                    }
+                    None => (contents, false),
+                };
+                Self::check_contents(&contents, known_modules, &mut report);
+                if migrated {
+                    for diagnostic in report.errors.iter_mut().chain(&mut report.warnings) {
+                        diagnostic.position = None;
+                    }
+                }
+            }
+            Err(e) => report.errors.push(Diagnostic {
+                position: None,
+                message: format!("failed to read file: {}", e),
+            }),
+        }
+
+        report
+    }
+
+    fn check_contents(contents: &str, known_modules: &[&str], report: &mut ConfigReport) {
+        match Self::parse(contents) {
+            Ok((config, warnings)) => {
+                report.warnings.extend(warnings);
+                for name in &config.modules {
+                    if !known_modules.contains(&name.as_str()) {
+                        report.warnings.push(Diagnostic {
+                            position: Diagnostic::find_value(contents, name),
+                            message: format!("unknown module `{}`", name),
+                        });
+                    }
+                }
+            }
+            Err(error) => report.errors.push(error),
+        }
     }
 
     pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
-        let config_path = Self::get_config_path();
+        self.save_to(&Self::get_config_path())
+    }
 
+    pub fn save_to(&self, config_path: &Path) -> Result<(), Box<dyn std::error::Error>> {
         // Create config directory if it doesn't exist
         if let Some(parent) = config_path.parent() {
             fs::create_dir_all(parent)?;
@@ -128,10 +537,100 @@ impl Config {
         Ok(())
     }
 
-    fn get_config_path() -> PathBuf {
+    /// Directory holding user theme files, next to the config file
+    pub fn themes_dir(config_path: &Path) -> PathBuf {
+        config_path
+            .parent()
+            .unwrap_or(Path::new("."))
+            .join("themes")
+    }
+
+    pub fn get_config_path() -> PathBuf {
         let mut config_dir = dirs::config_dir().unwrap_or_else(|| PathBuf::from("."));
         config_dir.push("hxfetch");
         config_dir.push("config.toml");
         config_dir
     }
 }
+
+/// A problem found in a config file, with its 1-based position when known
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    pub position: Option<(usize, usize)>,
+    pub message: String,
+}
+
+impl Diagnostic {
+    fn from_toml_error(contents: &str, error: &toml::de::Error) -> Self {
+        Self {
+            position: error
+                .span()
+                .map(|span| Self::line_column(contents, span.start)),
+            message: error
+                .message()
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .collect::<Vec<_>>()
+                .join(", "),
+        }
+    }
+
+    fn line_column(contents: &str, offset: usize) -> (usize, usize) {
+        let before = &contents[..offset.min(contents.len())];
+        let line = before.matches('\n').count() + 1;
+        let column = before
+            .rsplit('\n')
+            .next()
+            .map(|l| l.chars().count())
+            .unwrap_or(0)
+            + 1;
+        (line, column)
+    }
+
+    /// Best-effort position of a dotted key such as `probe_timeouts.gpu`
+    fn find_key(contents: &str, key: &str) -> Option<(usize, usize)> {
+        let name = key.rsplit('.').next().unwrap_or(key);
+
+        contents.lines().enumerate().find_map(|(index, line)| {
+            let trimmed = line.trim_start();
+            let rest = trimmed.strip_prefix(name)?;
+            if rest.trim_start().starts_with('=') {
+                Some((index + 1, line.len() - trimmed.len() + 1))
+            } else {
+                None
+            }
+        })
+    }
+
+    /// Best-effort position of a quoted string value such as a module name
+    fn find_value(contents: &str, value: &str) -> Option<(usize, usize)> {
+        let quoted = format!("\"{}\"", value);
+        contents.lines().enumerate().find_map(|(index, line)| {
+            line.find(&quoted)
+                .map(|offset| (index + 1, line[..offset].chars().count() + 1))
+        })
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.position {
+            Some((line, column)) => write!(f, "line {}, column {}: {}", line, column, self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+/// Result of `Config::check`
+pub struct ConfigReport {
+    pub path: PathBuf,
+    pub errors: Vec<Diagnostic>,
+    pub warnings: Vec<Diagnostic>,
+}
+
+impl ConfigReport {
+    pub fn is_ok(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
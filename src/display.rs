@@ -1,290 +1,650 @@
+use crate::color::{self, ColorChoice};
 use crate::config::Config;
-use crate::logo::LogoDisplay;
+use crate::layout::{Segment, Template};
+use crate::logo::{LogoDisplay, LogoPosition};
+use crate::modules::{self, field_pointer, Line, Module, Section};
 use crate::redactor::DataRedactor;
-use crate::sysinfo::SystemInfo;
-use colored::*;
+use crate::sysinfo::{
+    CpuInfo, DiskInfo, GpuInfo, InterfaceInfo, MemoryInfo, NetworkInfo, PackageCount, StorageInfo,
+    SystemInfo, UsageBucket,
+};
+use crate::terminal;
+use crate::theme::Theme;
+use serde_json::Value;
+
+const GIB: f64 = 1024.0 * 1024.0 * 1024.0;
+/// Degrees Celsius below the critical temperature from which it is highlighted
+const TEMPERATURE_WARN_MARGIN: f64 = 10.0;
+
+/// How close a value is to its limit
+enum Level {
+    Normal,
+    Warning,
+    Critical,
+}
 
 pub struct Display {
     config: Config,
+    theme: Theme,
 }
 
 impl Display {
-    pub fn new(config: Config) -> Self {
-        Self { config }
+    pub fn new(config: Config, theme: Theme) -> Self {
+        Self { config, theme }
     }
 
-    pub fn show(&self, system_info: &SystemInfo, screenshot_mode: bool) {
+    pub fn show(&self, system_info: &SystemInfo, modules: &[&dyn Module], screenshot_mode: bool) {
         // Redact sensitive information if in screenshot mode
         let display_info = if screenshot_mode {
-            DataRedactor::redact_system_info(system_info, &self.config)
+            DataRedactor::redact_system_info(system_info, &self.config, modules)
         } else {
             system_info.clone()
         };
 
-        let logo = LogoDisplay::show_logo(&display_info.os, &self.config);
-        let info_lines = self.format_system_info(&display_info, screenshot_mode);
+        let logo = LogoDisplay::show_logo(&display_info.os.pretty_name, &self.config);
+        let info_lines = self.format_system_info(&display_info, modules, screenshot_mode);
+
+        for line in self.compose(&logo, &info_lines, terminal::width()) {
+            println!("{}", line);
+        }
+    }
 
-        // Find the maximum width of the ASCII art (excluding ANSI escape sequences)
-        let max_logo_width = logo
+    /// Places the logo around the info lines according to `logo_position`,
+    /// fitted to a terminal of `width` columns
+    pub fn compose(
+        &self,
+        logo: &[String],
+        info_lines: &[String],
+        width: Option<usize>,
+    ) -> Vec<String> {
+        let logo_width = logo
             .iter()
-            .map(|line| Self::visual_width(line))
+            .map(|line| terminal::visual_width(line))
             .max()
             .unwrap_or(0);
+        let padding = " ".repeat(self.config.logo_padding);
+        let logo: Vec<String> = logo
+            .iter()
+            .map(|line| {
+                let line = self.theme.logo.paint(color::prepare_escapes(line));
+                format!("{}{}", padding, line)
+            })
+            .collect();
+        let logo_width = logo_width + self.config.logo_padding;
+
+        // Narrow terminals move the logo out of the way
+        let mut position = self.config.logo_position;
+        if let Some(width) = width {
+            if width < self.config.hide_logo_below_columns {
+                position = LogoPosition::Hidden;
+            } else if width < self.config.stack_below_columns
+                && matches!(position, LogoPosition::Left | LogoPosition::Right)
+            {
+                position = LogoPosition::Top;
+            }
+            if logo_width > width {
+                position = LogoPosition::Hidden;
+            }
+        }
 
-        // Add extra spacing (gap) after the logo
-        let gap = 8; // Adjust this number to make the gap wider/narrower
-        let total_width = max_logo_width + gap;
-
-        let max_lines = std::cmp::max(logo.len(), info_lines.len());
-        let empty_string = String::new();
-
-        for i in 0..max_lines {
-            let logo_line = logo.get(i).unwrap_or(&empty_string);
-            let info_line = info_lines.get(i).unwrap_or(&empty_string);
-
-            // Calculate how much padding we need based on visual width
-            let logo_visual_width = Self::visual_width(logo_line);
-            let needed_padding = total_width.saturating_sub(logo_visual_width);
+        let side_width = logo_width + self.config.logo_gap;
+        let info_lines = match (position, width) {
+            (LogoPosition::Left | LogoPosition::Right, Some(width)) => {
+                self.fit_lines(info_lines, width.saturating_sub(side_width))
+            }
+            (_, Some(width)) => self.fit_lines(info_lines, width),
+            (_, None) => info_lines.to_vec(),
+        };
 
-            println!(
-                "{}{} {}",
-                logo_line.color(self.config.logo_color.to_colored_string()),
-                " ".repeat(needed_padding),
-                info_line
-            );
+        match position {
+            LogoPosition::Hidden => info_lines,
+            LogoPosition::Top => [logo, vec![String::new()], info_lines].concat(),
+            LogoPosition::Bottom => [info_lines, vec![String::new()], logo].concat(),
+            LogoPosition::Left | LogoPosition::Right => {
+                let rows = std::cmp::max(logo.len(), info_lines.len());
+                let align = self.config.logo_align;
+                let logo_offset = align.offset(logo.len(), rows);
+                let info_offset = align.offset(info_lines.len(), rows);
+                let info_width = info_lines
+                    .iter()
+                    .map(|line| terminal::visual_width(line))
+                    .max()
+                    .unwrap_or(0);
+
+                (0..rows)
+                    .map(|row| {
+                        let logo_line = row
+                            .checked_sub(logo_offset)
+                            .and_then(|i| logo.get(i))
+                            .map(String::as_str)
+                            .unwrap_or("");
+                        let info_line = row
+                            .checked_sub(info_offset)
+                            .and_then(|i| info_lines.get(i))
+                            .map(String::as_str)
+                            .unwrap_or("");
+
+                        // Pad by visual width, both sides may contain escapes
+                        let (first, first_width, second) = match position {
+                            LogoPosition::Left => (logo_line, logo_width, info_line),
+                            _ => (info_line, info_width, logo_line),
+                        };
+                        if second.is_empty() {
+                            return first.to_string();
+                        }
+                        let fill = (first_width + self.config.logo_gap)
+                            .saturating_sub(terminal::visual_width(first));
+                        format!("{}{}{}", first, " ".repeat(fill), second)
+                    })
+                    .collect()
+            }
         }
     }
 
-    fn format_system_info(&self, info: &SystemInfo, screenshot_mode: bool) -> Vec<String> {
-        let mut lines = Vec::new();
-
-        // Title with hostname
-        lines.push(format!(
-            "{}@{}",
-            info.hostname
-                .color(self.config.accent_color.to_colored_string())
-                .bold(),
-            info.hostname
-                .color(self.config.accent_color.to_colored_string())
-                .bold()
-        ));
-
-        // Separator
-        lines.push(
-            "-".repeat(info.hostname.len() * 2 + 1)
-                .color(self.config.separator_color.to_colored_string())
-                .to_string(),
-        );
-
-        // System information
-        if self.config.show_os {
-            lines.push(self.format_info_line("OS", &info.os));
-        }
+    /// Truncates or wraps the info lines to `width` columns
+    fn fit_lines(&self, lines: &[String], width: usize) -> Vec<String> {
+        lines
+            .iter()
+            .flat_map(|line| terminal::fit(line, width, self.config.overflow))
+            .collect()
+    }
 
-        if self.config.show_kernel {
-            lines.push(self.format_info_line("Kernel", &info.kernel));
+    pub fn format_system_info(
+        &self,
+        info: &SystemInfo,
+        modules: &[&dyn Module],
+        screenshot_mode: bool,
+    ) -> Vec<String> {
+        if !self.config.layout.is_empty() {
+            let mut lines = self.format_layout(info, modules, screenshot_mode);
+            lines.extend(self.format_notices(info, screenshot_mode));
+            return lines;
         }
 
-        if self.config.show_uptime {
-            lines.push(self.format_info_line("Uptime", &info.uptime));
-        }
+        let mut lines = vec![self.format_title(info), self.format_separator(info)];
 
-        if self.config.show_cpu {
-            lines.push(self.format_info_line("CPU", &info.cpu));
+        // System information
+        for module in modules.iter().filter(|m| m.section() == Section::System) {
+            lines.extend(self.render_module(*module, info, screenshot_mode));
         }
 
-        if self.config.show_memory {
-            lines.push(self.format_info_line("Memory", &info.memory));
-        }
+        // Security information
+        let security_modules: Vec<&&dyn Module> = modules
+            .iter()
+            .filter(|m| m.section() == Section::Security)
+            .collect();
 
-        if self.config.show_storage {
-            lines.push(self.format_info_line("Storage", &info.storage));
-        }
+        if !security_modules.is_empty() {
+            lines.push(String::new()); // Spacing
+            lines.push(self.theme.accent.paint("Security:").bold().to_string());
+
+            let mut hidden = false;
+            for module in security_modules {
+                // Only show detailed security info when NOT in screenshot mode,
+                // defensive measures like AppArmor or updates are safe to share
+                if screenshot_mode && module.sensitive() {
+                    hidden = true;
+                    continue;
+                }
+                lines.extend(self.render_module(*module, info, screenshot_mode));
+            }
 
-        if self.config.show_shell {
-            lines.push(self.format_info_line("Shell", &info.shell));
+            // In screenshot mode, show a general security summary instead of details
+            if hidden {
+                lines.push(
+                    ColorChoice::Yellow
+                        .paint("Additional security details hidden in screenshot mode")
+                        .italic()
+                        .to_string(),
+                );
+            }
         }
 
-        if self.config.show_desktop && info.desktop != "Unknown" {
-            lines.push(self.format_info_line("DE", &info.desktop));
-        }
+        lines.extend(self.format_notices(info, screenshot_mode));
 
-        if self.config.show_terminal {
-            lines.push(self.format_info_line("Terminal", &info.terminal));
+        // Color palette and other trailing modules
+        for module in modules.iter().filter(|m| m.section() == Section::Footer) {
+            lines.extend(self.render_module(*module, info, screenshot_mode));
         }
 
-        if self.config.show_gpu && info.gpu != "Unknown" {
-            lines.push(self.format_info_line("GPU", &info.gpu));
-        }
+        lines
+    }
 
-        if self.config.show_temperature && info.temperature != "Unknown" {
-            lines.push(self.format_info_line("Temperature", &info.temperature));
-        }
+    /// Timeout report and screenshot notice, printed after the info lines
+    fn format_notices(&self, info: &SystemInfo, screenshot_mode: bool) -> Vec<String> {
+        let mut lines = Vec::new();
 
-        if self.config.show_network && info.network != "Unknown" {
-            lines.push(self.format_info_line("Network", &info.network));
+        // Report probes that were killed so "Unknown" values can be told apart
+        if !info.timeouts.is_empty() {
+            let modules: Vec<&str> = info.timeouts.iter().map(|t| t.module.as_str()).collect();
+            lines.push(String::new());
+            lines.push(
+                ColorChoice::Yellow
+                    .paint(format!("Timed out: {}", modules.join(", ")))
+                    .italic()
+                    .to_string(),
+            );
         }
 
-        if self.config.show_packages && info.packages != "Unknown" {
-            lines.push(self.format_info_line("Packages", &info.packages));
-        }
+        // Add some spacing
+        lines.push(String::new());
 
-        // Security information
-        if self.config.show_security {
-            lines.push(String::new()); // Spacing
+        // Screenshot mode notice
+        if screenshot_mode {
             lines.push(
-                "Security:"
-                    .color(self.config.accent_color.to_colored_string())
+                ColorChoice::Yellow
+                    .paint(DataRedactor::get_redaction_notice())
                     .bold()
                     .to_string(),
             );
+            lines.push(String::new());
+        }
 
-            if !screenshot_mode {
-                // Only show detailed security info when NOT in screenshot mode
-                if self.config.show_firewall && info.security.firewall_status != "None detected" {
-                    lines.push(self.format_info_line("Firewall", &info.security.firewall_status));
-                }
+        lines
+    }
 
-                if self.config.show_selinux && info.security.selinux_status != "Not available" {
-                    lines.push(self.format_info_line("SELinux", &info.security.selinux_status));
-                }
+    /// `user@hostname` header
+    fn format_title(&self, info: &SystemInfo) -> String {
+        let accent = self.theme.accent;
+        format!(
+            "{}@{}",
+            accent.paint(&info.user).bold(),
+            accent.paint(&info.hostname).bold()
+        )
+    }
 
-                if self.config.show_ssh {
-                    lines.push(self.format_info_line("SSH", &info.security.ssh_status));
-                }
+    fn format_separator(&self, info: &SystemInfo) -> String {
+        let width = terminal::visual_width(&info.user) + 1 + terminal::visual_width(&info.hostname);
+        self.theme.separator.paint("-".repeat(width)).to_string()
+    }
 
-                if self.config.show_fail2ban && info.security.fail2ban_status != "Not installed" {
-                    lines.push(self.format_info_line("Fail2Ban", &info.security.fail2ban_status));
-                }
+    /// Renders the `layout` templates from the config
+    fn format_layout(
+        &self,
+        info: &SystemInfo,
+        modules: &[&dyn Module],
+        screenshot_mode: bool,
+    ) -> Vec<String> {
+        let document = serde_json::to_value(info).unwrap_or(Value::Null);
+        let mut lines = Vec::new();
 
-                if self.config.show_secure_boot && info.security.secure_boot != "Unknown" {
-                    lines.push(self.format_info_line("Secure Boot", &info.security.secure_boot));
-                }
+        for block in &self.config.layout {
+            let rendered: Vec<String> = block
+                .lines
+                .iter()
+                .filter_map(|line| Template::parse(line).ok())
+                .filter_map(|template| {
+                    self.render_template(&template, info, &document, modules, screenshot_mode)
+                })
+                .collect();
+
+            if rendered.is_empty() {
+                continue;
             }
-
-            // These are safe to show in screenshot mode (defensive security is good to share)
-            if self.config.show_apparmor && info.security.apparmor_status != "Not available" {
-                lines.push(self.format_info_line("AppArmor", &info.security.apparmor_status));
+            if !lines.is_empty() {
+                lines.push(String::new());
             }
-
-            if self.config.show_updates {
-                lines.push(self.format_info_line("Updates", &info.security.package_updates));
+            if let Some(title) = &block.title {
+                lines.push(self.theme.accent.paint(title).bold().to_string());
             }
+            lines.extend(rendered);
+        }
 
-            if self.config.show_encryption && info.security.disk_encryption != "None detected" {
-                lines.push(self.format_info_line("Encryption", &info.security.disk_encryption));
+        lines
+    }
+
+    /// One template line, or `None` when it is dropped because a required
+    /// value is unknown or a value is hidden in screenshot mode
+    fn render_template(
+        &self,
+        template: &Template,
+        info: &SystemInfo,
+        document: &Value,
+        modules: &[&dyn Module],
+        screenshot_mode: bool,
+    ) -> Option<String> {
+        if screenshot_mode {
+            let hidden = template.fields().any(|name| {
+                modules
+                    .iter()
+                    .any(|module| module.sensitive() && modules::references(*module, name))
+            });
+            if hidden {
+                return None;
             }
+        }
 
-            // In screenshot mode, show a general security summary instead of details
-            if screenshot_mode {
-                lines.push(
-                    "Additional security details hidden in screenshot mode"
-                        .color(ColorChoice::Yellow.to_colored_string())
-                        .italic()
-                        .to_string(),
-                );
+        let mut line = String::new();
+        for segment in &template.segments {
+            match segment {
+                Segment::Text(text) => line.push_str(text),
+                Segment::Label(label) => {
+                    line.push_str(&format!("{}:", self.theme.label.paint(label).bold()))
+                }
+                Segment::Field {
+                    name,
+                    required,
+                    fallback,
+                } => match self.resolve_field(name, info, document, modules, screenshot_mode) {
+                    Some(value) => line.push_str(&value),
+                    None if *required => return None,
+                    None => {
+                        line.push_str(&self.format_value(fallback.as_deref().unwrap_or("Unknown")))
+                    }
+                },
             }
         }
 
-        // Add some spacing
-        lines.push(String::new());
+        Some(line)
+    }
 
-        // Screenshot mode notice
-        if screenshot_mode {
-            lines.push(
-                DataRedactor::get_redaction_notice()
-                    .color(ColorChoice::Yellow.to_colored_string())
-                    .bold()
-                    .to_string(),
-            );
-            lines.push(String::new());
+    /// Looks up a template value: `title` and `separator`, the formatted
+    /// output of an enabled module such as `memory`, or a dotted path into the
+    /// collected data such as `os.version`. Unknown values yield `None`.
+    fn resolve_field(
+        &self,
+        name: &str,
+        info: &SystemInfo,
+        document: &Value,
+        modules: &[&dyn Module],
+        screenshot_mode: bool,
+    ) -> Option<String> {
+        match name {
+            "title" => return Some(self.format_title(info)),
+            "separator" => return Some(self.format_separator(info)),
+            _ => {}
         }
 
-        // Color palette
-        if self.config.show_colors {
-            lines.push(self.format_color_palette());
+        if let Some(module) = modules.iter().find(|module| module.name() == name) {
+            let mut values = Vec::new();
+            for line in module.render(info, screenshot_mode) {
+                match line {
+                    Line::Info { value, .. } => values.push(value),
+                    Line::Usage {
+                        value, used, total, ..
+                    } => values.push(self.format_usage_value(&value, used, total)),
+                    Line::Temperature { celsius, .. } => {
+                        values.push(self.config.temperature_unit.format(celsius))
+                    }
+                    // Already colored, printed as-is
+                    Line::Palette => return color::enabled().then(|| self.format_color_palette()),
+                }
+            }
+            return Self::known(values.join(", ")).map(|value| self.format_value(&value));
         }
 
-        lines
+        let value = match document.pointer(&field_pointer(name))? {
+            Value::String(text) => text.clone(),
+            Value::Number(number) => number.to_string(),
+            Value::Bool(flag) => flag.to_string(),
+            Value::Array(items) => items
+                .iter()
+                .filter_map(|item| match item {
+                    Value::String(text) => Some(text.clone()),
+                    Value::Number(number) => Some(number.to_string()),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+                .join(", "),
+            Value::Null | Value::Object(_) => return None,
+        };
+        Self::known(value).map(|value| self.format_value(&value))
+    }
+
+    fn known(value: String) -> Option<String> {
+        if value.is_empty() || value == "Unknown" {
+            None
+        } else {
+            Some(value)
+        }
+    }
+
+    fn format_value(&self, value: &str) -> String {
+        self.theme.value.paint(value).to_string()
+    }
+
+    fn render_module(
+        &self,
+        module: &dyn Module,
+        info: &SystemInfo,
+        screenshot_mode: bool,
+    ) -> Vec<String> {
+        module
+            .render(info, screenshot_mode)
+            .into_iter()
+            .filter_map(|line| match line {
+                Line::Info { label, value } => Some(self.format_info_line(&label, &value)),
+                Line::Usage {
+                    label,
+                    value,
+                    used,
+                    total,
+                } => Some(
+                    self.format_info_line(&label, &self.format_usage_value(&value, used, total)),
+                ),
+                Line::Temperature {
+                    label,
+                    celsius,
+                    critical,
+                } => Some(format!(
+                    "{}: {}",
+                    self.theme.label.paint(&label).bold(),
+                    self.format_temperature(celsius, critical)
+                )),
+                // Blocks of nothing but color, pointless without it
+                Line::Palette => color::enabled().then(|| self.format_color_palette()),
+            })
+            .collect()
     }
 
     fn format_info_line(&self, label: &str, value: &str) -> String {
         format!(
             "{}: {}",
-            label
-                .color(self.config.label_color.to_colored_string())
-                .bold(),
-            value.color(self.config.value_color.to_colored_string())
+            self.theme.label.paint(label).bold(),
+            self.theme.value.paint(value)
         )
     }
 
-    fn format_color_palette(&self) -> String {
-        let colors = [
-            "███".black(),
-            "███".red(),
-            "███".green(),
-            "███".yellow(),
-            "███".blue(),
-            "███".magenta(),
-            "███".cyan(),
-            "███".white(),
-        ];
+    pub fn format_uptime(uptime_seconds: u64) -> String {
+        let days = uptime_seconds / 86400;
+        let hours = (uptime_seconds % 86400) / 3600;
+        let minutes = (uptime_seconds % 3600) / 60;
 
-        colors
-            .iter()
-            .map(|c| c.to_string())
-            .collect::<Vec<_>>()
-            .join("")
+        if days > 0 {
+            format!("{} days, {} hours, {} minutes", days, hours, minutes)
+        } else if hours > 0 {
+            format!("{} hours, {} minutes", hours, minutes)
+        } else {
+            format!("{} minutes", minutes)
+        }
     }
 
-    // Calculate visual width of a string, excluding ANSI escape sequences
-    fn visual_width(text: &str) -> usize {
-        let mut result = String::new();
-        let mut chars = text.chars();
+    pub fn format_cpu(cpu: &Option<CpuInfo>) -> String {
+        match cpu {
+            Some(cpu) if cpu.cores != cpu.threads => {
+                format!(
+                    "{} ({} cores, {} threads)",
+                    cpu.brand, cpu.cores, cpu.threads
+                )
+            }
+            Some(cpu) => format!("{} ({} cores)", cpu.brand, cpu.threads),
+            None => "Unknown".to_string(),
+        }
+    }
 
-        while let Some(ch) = chars.next() {
-            if ch == '\x1b' {
-                // Skip ANSI escape sequence
-                if chars.next() == Some('[') {
-                    // Continue until we find the end of the escape sequence
-                    for escape_char in chars.by_ref() {
-                        if escape_char.is_ascii_alphabetic() || escape_char == 'm' {
-                            break;
-                        }
-                    }
-                }
-            } else {
-                result.push(ch);
+    pub fn format_gb(bytes: u64) -> String {
+        format!("{:.1} GB", bytes as f64 / GIB)
+    }
+
+    fn format_usage_percent(used: u64, total: u64) -> u32 {
+        if total == 0 {
+            0
+        } else {
+            (used as f64 / total as f64 * 100.0).round() as u32
+        }
+    }
+
+    pub fn format_memory(memory: &MemoryInfo) -> String {
+        Self::format_amounts(memory.used, memory.total, memory.bucket.as_ref())
+    }
+
+    pub fn format_storage(storage: &Option<StorageInfo>) -> String {
+        let Some(storage) = storage else {
+            return "Unknown".to_string();
+        };
+
+        let usage = Self::format_amounts(storage.used, storage.total, storage.bucket.as_ref());
+        if storage.bucket.is_none() && storage.disks.len() > 1 {
+            format!("{} ({} drives)", usage, storage.disks.len())
+        } else {
+            usage
+        }
+    }
+
+    /// Usage of one filesystem followed by its type, e.g.
+    /// `12.0 GB / 100.0 GB (ext4, read-only)`
+    pub fn format_disk(disk: &DiskInfo) -> String {
+        let usage = Self::format_amounts(disk.used, disk.total, disk.bucket.as_ref());
+        if disk.read_only {
+            format!("{} ({}, read-only)", usage, disk.file_system)
+        } else {
+            format!("{} ({})", usage, disk.file_system)
+        }
+    }
+
+    /// `4.2 GB / 16.0 GB`, or `26% of 16-32 GB` once redacted
+    fn format_amounts(
+        used: Option<u64>,
+        total: Option<u64>,
+        bucket: Option<&UsageBucket>,
+    ) -> String {
+        if let Some(bucket) = bucket {
+            return format!("{}% of {}", bucket.percent, bucket.size);
+        }
+        match (used, total) {
+            (Some(used), Some(total)) => {
+                format!("{} / {}", Self::format_gb(used), Self::format_gb(total))
             }
+            _ => "Unknown".to_string(),
         }
+    }
 
-        result.chars().count()
+    /// `value` followed by the usage bar, when enabled
+    fn format_usage_value(&self, value: &str, used: u64, total: u64) -> String {
+        if !self.config.usage_bars || total == 0 {
+            return value.to_string();
+        }
+        format!("{} {}", value, self.format_bar(used, total))
     }
-}
 
-#[derive(Clone, Copy, Debug, serde::Serialize, serde::Deserialize)]
-pub enum ColorChoice {
-    Red,
-    Green,
-    Blue,
-    Yellow,
-    Magenta,
-    Cyan,
-    White,
-    Black,
-}
+    /// `[██████░░░░] 62%`, with the filled part in the theme's green, yellow or
+    /// red depending on the warn and crit percentages
+    pub fn format_bar(&self, used: u64, total: u64) -> String {
+        let percent = Self::format_usage_percent(used, total);
+        let width = self.config.bar_width;
+        let filled = (used as f64 / total as f64 * width as f64).round() as usize;
+        let filled = filled.min(width);
+
+        let level = if percent >= self.config.bar_crit_percent {
+            Level::Critical
+        } else if percent >= self.config.bar_warn_percent {
+            Level::Warning
+        } else {
+            Level::Normal
+        };
+
+        format!(
+            "[{}{}] {}%",
+            self.level_color(level)
+                .paint(self.config.bar_fill.repeat(filled)),
+            self.config.bar_empty.repeat(width - filled),
+            percent
+        )
+    }
+
+    /// A temperature in the configured unit, yellow within
+    /// `TEMPERATURE_WARN_MARGIN` degrees of `critical` and red beyond it
+    pub fn format_temperature(&self, celsius: f64, critical: Option<f64>) -> String {
+        let text = self.config.temperature_unit.format(celsius);
+        let color = match critical {
+            Some(critical) if celsius >= critical => self.level_color(Level::Critical),
+            Some(critical) if celsius >= critical - TEMPERATURE_WARN_MARGIN => {
+                self.level_color(Level::Warning)
+            }
+            _ => self.theme.value,
+        };
+        color.paint(text).to_string()
+    }
+
+    /// Palette red, yellow or green, or the named colors for short palettes
+    fn level_color(&self, level: Level) -> ColorChoice {
+        let (index, fallback) = match level {
+            Level::Critical => (1, ColorChoice::Red),
+            Level::Warning => (3, ColorChoice::Yellow),
+            Level::Normal => (2, ColorChoice::Green),
+        };
+        self.theme.palette.get(index).copied().unwrap_or(fallback)
+    }
 
-impl ColorChoice {
-    pub fn to_colored_string(self) -> Color {
-        match self {
-            ColorChoice::Red => Color::Red,
-            ColorChoice::Green => Color::Green,
-            ColorChoice::Blue => Color::Blue,
-            ColorChoice::Yellow => Color::Yellow,
-            ColorChoice::Magenta => Color::Magenta,
-            ColorChoice::Cyan => Color::Cyan,
-            ColorChoice::White => Color::White,
-            ColorChoice::Black => Color::Black,
+    pub fn format_network(network: &NetworkInfo) -> String {
+        match network.addresses.first() {
+            Some(address) => format!("{} ({})", address, network.interface),
+            None => network.interface.clone(),
         }
     }
+
+    /// `192.168.1.42, 2001:db8::42 (up, 1000 Mb/s, 3c:a9:f4:12:34:56)`
+    pub fn format_interface(interface: &InterfaceInfo) -> String {
+        let mut details = vec![interface.state.clone()];
+        if interface.wireless {
+            details.push("wireless".to_string());
+        }
+        if let Some(speed) = interface.speed_mbps {
+            details.push(format!("{} Mb/s", speed));
+        }
+        details.extend(interface.mac.clone());
+
+        let addresses: Vec<&str> = interface
+            .ipv4
+            .iter()
+            .chain(&interface.ipv6)
+            .map(String::as_str)
+            .collect();
+        if addresses.is_empty() {
+            format!("({})", details.join(", "))
+        } else {
+            format!("{} ({})", addresses.join(", "), details.join(", "))
+        }
+    }
+
+    pub fn format_gpu(gpu: &GpuInfo) -> String {
+        let details: Vec<String> = gpu
+            .driver
+            .iter()
+            .cloned()
+            .chain(gpu.vram_bytes.map(Self::format_gb))
+            .collect();
+        if details.is_empty() {
+            gpu.name.clone()
+        } else {
+            format!("{} ({})", gpu.name, details.join(", "))
+        }
+    }
+
+    pub fn format_packages(packages: &[PackageCount]) -> String {
+        packages
+            .iter()
+            .map(|p| format!("{} ({})", p.count, p.manager))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    pub fn format_color_palette(&self) -> String {
+        self.theme
+            .palette
+            .iter()
+            .map(|color| color.paint("███").to_string())
+            .collect::<Vec<_>>()
+            .join("")
+    }
 }
@@ -0,0 +1,81 @@
+use crate::modules::{field_pointer, Module};
+use crate::sysinfo::SystemInfo;
+use serde_json::{Map, Value};
+
+/// Version of the JSON document layout. Bump it whenever a key is renamed,
+/// removed or changes type so consumers can detect incompatible output.
+pub const JSON_SCHEMA_VERSION: u32 = 6;
+
+pub struct JsonExporter;
+
+impl JsonExporter {
+    /// Builds the machine-readable document for `info`.
+    ///
+    /// Only the values of the given modules are included, and in screenshot
+    /// mode sensitive modules are dropped just like in the text output.
+    /// `info` is expected to already be redacted.
+    ///
+    /// Keys keep the order they are inserted in: `schema_version`,
+    /// `redacted`, `user`, `hostname` and `timeouts` first, then the module
+    /// values in the order of `modules`.
+    pub fn export(info: &SystemInfo, modules: &[&dyn Module], screenshot_mode: bool) -> Value {
+        let mut document = Map::new();
+        document.insert(
+            "schema_version".to_string(),
+            Value::from(JSON_SCHEMA_VERSION),
+        );
+        document.insert("redacted".to_string(), Value::Bool(screenshot_mode));
+
+        let Ok(serialized) = serde_json::to_value(info) else {
+            return Value::Object(document);
+        };
+
+        // User and hostname are part of the header and always shown, timeouts
+        // are reported so consumers can tell a slow probe from a missing value
+        for key in ["user", "hostname", "timeouts"] {
+            if let Some(value) = serialized.get(key) {
+                document.insert(key.to_string(), value.clone());
+            }
+        }
+
+        for module in modules {
+            if screenshot_mode && module.sensitive() {
+                continue;
+            }
+
+            if let Some(field) = module.field() {
+                if let Some(value) = serialized.pointer(&field_pointer(field)) {
+                    Self::insert_path(&mut document, field, value.clone());
+                }
+            }
+        }
+
+        Value::Object(document)
+    }
+
+    pub fn to_string_pretty(
+        info: &SystemInfo,
+        modules: &[&dyn Module],
+        screenshot_mode: bool,
+    ) -> String {
+        serde_json::to_string_pretty(&Self::export(info, modules, screenshot_mode))
+            .unwrap_or_else(|_| "{}".to_string())
+    }
+
+    /// Inserts `value` at a dotted `path`, creating intermediate objects
+    fn insert_path(document: &mut Map<String, Value>, path: &str, value: Value) {
+        match path.split_once('.') {
+            Some((head, rest)) => {
+                let entry = document
+                    .entry(head.to_string())
+                    .or_insert_with(|| Value::Object(Map::new()));
+                if let Value::Object(section) = entry {
+                    Self::insert_path(section, rest, value);
+                }
+            }
+            None => {
+                document.insert(path.to_string(), value);
+            }
+        }
+    }
+}
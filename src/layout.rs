@@ -0,0 +1,125 @@
+use serde::{Deserialize, Serialize};
+
+/// A group of template lines from the `layout` config, optionally under a
+/// heading. Blocks are separated by a blank line, and a block whose lines
+/// were all dropped disappears together with its heading.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LayoutBlock {
+    pub title: Option<String>,
+    pub lines: Vec<String>,
+}
+
+/// One piece of a parsed template line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Segment {
+    /// Literal text, printed as-is
+    Text(String),
+    /// `{label:OS}`, printed as a colored `OS:`
+    Label(String),
+    /// `{name}`, `{name?}` or `{name|fallback}`: a value looked up by name.
+    /// With `required` set the whole line is dropped when the value is
+    /// unknown; otherwise the fallback (or "Unknown") is shown.
+    Field {
+        name: String,
+        required: bool,
+        fallback: Option<String>,
+    },
+}
+
+/// A parsed layout line such as `"{label:OS} {os.name} {os.version?}"`.
+/// `{{` and `}}` produce literal braces.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Template {
+    pub segments: Vec<Segment>,
+}
+
+impl Template {
+    pub fn parse(line: &str) -> Result<Self, String> {
+        let mut segments = Vec::new();
+        let mut text = String::new();
+        let mut chars = line.char_indices().peekable();
+
+        while let Some((start, ch)) = chars.next() {
+            match ch {
+                '{' if chars.peek().map(|(_, c)| *c) == Some('{') => {
+                    chars.next();
+                    text.push('{');
+                }
+                '}' if chars.peek().map(|(_, c)| *c) == Some('}') => {
+                    chars.next();
+                    text.push('}');
+                }
+                '{' => {
+                    let mut placeholder = String::new();
+                    let mut closed = false;
+                    for (_, c) in chars.by_ref() {
+                        if c == '}' {
+                            closed = true;
+                            break;
+                        }
+                        placeholder.push(c);
+                    }
+                    if !closed {
+                        return Err(format!("unclosed `{{` at position {}", start + 1));
+                    }
+
+                    if !text.is_empty() {
+                        segments.push(Segment::Text(std::mem::take(&mut text)));
+                    }
+                    segments.push(Self::parse_placeholder(&placeholder)?);
+                }
+                '}' => {
+                    return Err(format!(
+                        "unmatched `}}` at position {} (use `}}}}` for a literal brace)",
+                        start + 1
+                    ))
+                }
+                _ => text.push(ch),
+            }
+        }
+
+        if !text.is_empty() {
+            segments.push(Segment::Text(text));
+        }
+
+        Ok(Self { segments })
+    }
+
+    fn parse_placeholder(placeholder: &str) -> Result<Segment, String> {
+        if let Some(label) = placeholder.strip_prefix("label:") {
+            return Ok(Segment::Label(label.to_string()));
+        }
+
+        let (name, fallback) = match placeholder.split_once('|') {
+            Some((name, fallback)) => (name, Some(fallback.to_string())),
+            None => (placeholder, None),
+        };
+        let (name, required) = match name.trim().strip_suffix('?') {
+            Some(name) => (name, true),
+            None => (name.trim(), false),
+        };
+
+        let valid = !name.is_empty()
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.');
+        if !valid {
+            return Err(format!("invalid placeholder `{{{}}}`", placeholder));
+        }
+
+        Ok(Segment::Field {
+            name: name.to_string(),
+            required,
+            fallback,
+        })
+    }
+
+    /// Names of the values this line looks up
+    pub fn fields(&self) -> impl Iterator<Item = &str> {
+        self.segments.iter().filter_map(|segment| match segment {
+            Segment::Field { name, .. } => Some(name.as_str()),
+            _ => None,
+        })
+    }
+}
@@ -1,5 +1,60 @@
 use crate::ascii::AsciiArt;
 use crate::config::Config as AppConfig;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+/// Where the logo is drawn relative to the info lines
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogoPosition {
+    #[default]
+    Left,
+    Right,
+    Top,
+    Bottom,
+    #[serde(rename = "none")]
+    Hidden,
+}
+
+impl FromStr for LogoPosition {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        match text {
+            "left" => Ok(LogoPosition::Left),
+            "right" => Ok(LogoPosition::Right),
+            "top" => Ok(LogoPosition::Top),
+            "bottom" => Ok(LogoPosition::Bottom),
+            "none" => Ok(LogoPosition::Hidden),
+            _ => Err(format!(
+                "invalid logo position `{}`, expected left, right, top, bottom or none",
+                text
+            )),
+        }
+    }
+}
+
+/// How the shorter of the logo and the info lines is placed next to the other
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogoAlign {
+    #[default]
+    Top,
+    Center,
+    Bottom,
+}
+
+impl LogoAlign {
+    /// Blank rows above a block of `len` rows in a column of `rows`
+    pub fn offset(self, len: usize, rows: usize) -> usize {
+        let spare = rows.saturating_sub(len);
+        match self {
+            LogoAlign::Top => 0,
+            LogoAlign::Center => spare / 2,
+            LogoAlign::Bottom => spare,
+        }
+    }
+}
 
 pub struct LogoDisplay;
 
@@ -1,17 +1,42 @@
 mod ascii;
 mod ascii_maker;
+mod cache;
+mod color;
 mod config;
 mod display;
+mod export;
+mod layout;
 mod logo;
 mod logo_config;
+mod migrate;
+mod modules;
+mod pci;
+mod probe;
 mod redactor;
 mod security;
 mod sysinfo;
+mod terminal;
+mod theme;
 
+#[cfg(test)]
+mod tests;
+
+use crate::cache::ProbeCache;
+use crate::color::ColorMode;
 use crate::config::Config;
 use crate::display::Display;
+use crate::export::JsonExporter;
+use crate::migrate::{ConfigMigrator, CONFIG_VERSION};
+use crate::modules::ModuleRegistry;
+use crate::probe::{ProbeContext, SystemRunner};
+use crate::redactor::DataRedactor;
 use crate::sysinfo::SystemInfo;
+use crate::theme::Theme;
 use clap::{Arg, Command};
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
 
 fn main() {
     let matches = Command::new("hxfetch")
@@ -25,6 +50,13 @@ fn main() {
                 .value_name("FILE")
                 .help("Use custom config file"),
         )
+        .arg(
+            Arg::new("profile")
+                .short('p')
+                .long("profile")
+                .value_name("NAME")
+                .help("Apply profiles/NAME.toml on top of the config"),
+        )
         .arg(
             Arg::new("no-config")
                 .long("no-config")
@@ -37,6 +69,47 @@ fn main() {
                 .help("Generate default config file")
                 .action(clap::ArgAction::SetTrue),
         )
+        .arg(
+            Arg::new("check-config")
+                .long("check-config")
+                .help("Validate the config file and report problems")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("set")
+                .long("set")
+                .value_name("KEY=VALUE")
+                .help("Override a config setting, e.g. --set probe_timeout_ms=500")
+                .action(clap::ArgAction::Append),
+        )
+        .arg(
+            Arg::new("show")
+                .long("show")
+                .value_name("MODULE")
+                .help("Show a module in addition to the configured ones")
+                .action(clap::ArgAction::Append),
+        )
+        .arg(
+            Arg::new("hide")
+                .long("hide")
+                .value_name("MODULE")
+                .help("Hide a configured module")
+                .action(clap::ArgAction::Append),
+        )
+        .arg(
+            Arg::new("theme")
+                .short('t')
+                .long("theme")
+                .value_name("NAME")
+                .help("Color theme, a built-in preset or a file in themes/"),
+        )
+        .arg(
+            Arg::new("logo-position")
+                .long("logo-position")
+                .value_name("POSITION")
+                .help("Where to draw the logo")
+                .value_parser(["left", "right", "top", "bottom", "none"]),
+        )
         .arg(
             Arg::new("screenshot")
                 .short('s')
@@ -44,12 +117,56 @@ fn main() {
                 .help("Screenshot mode - redact sensitive information")
                 .action(clap::ArgAction::SetTrue),
         )
+        .arg(
+            Arg::new("format")
+                .short('f')
+                .long("format")
+                .value_name("FORMAT")
+                .help("Output format")
+                .value_parser(["text", "json"])
+                .default_value("text"),
+        )
+        .arg(
+            Arg::new("color")
+                .long("color")
+                .value_name("WHEN")
+                .help("When to use colors (auto: only on a terminal without NO_COLOR)")
+                .value_parser(["auto", "always", "never"])
+                .default_value("auto"),
+        )
+        .arg(
+            Arg::new("refresh")
+                .long("refresh")
+                .help("Ignore cached module results and probe again")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("sysroot")
+                .long("sysroot")
+                .value_name("DIR")
+                .help("Read /proc, /sys and /etc from DIR instead of /"),
+        )
         .arg(
             Arg::new("ascii-maker")
                 .long("ascii-maker")
                 .help("Launch the ASCII art maker tool")
                 .action(clap::ArgAction::SetTrue),
         )
+        .subcommand(
+            Command::new("config")
+                .about("Manage the config file")
+                .subcommand_required(true)
+                .subcommand(
+                    Command::new("migrate")
+                        .about("Upgrade the config file to the current version")
+                        .arg(
+                            Arg::new("dry-run")
+                                .long("dry-run")
+                                .help("Show the changes without writing them")
+                                .action(clap::ArgAction::SetTrue),
+                        ),
+                ),
+        )
         .get_matches();
 
     if matches.get_flag("ascii-maker") {
@@ -57,26 +174,183 @@ fn main() {
         return;
     }
 
+    let color_mode: ColorMode = matches
+        .get_one::<String>("color")
+        .and_then(|mode| mode.parse().ok())
+        .unwrap_or(ColorMode::Auto);
+    color::set_enabled(color_mode.resolve(
+        std::env::var("NO_COLOR").ok().as_deref(),
+        std::env::var("CLICOLOR_FORCE").ok().as_deref(),
+        std::io::stdout().is_terminal(),
+    ));
+
+    let custom_config = matches.get_one::<String>("config").map(PathBuf::from);
+    let config_path = custom_config
+        .clone()
+        .unwrap_or_else(Config::get_config_path);
+    let profile = matches.get_one::<String>("profile").map(String::as_str);
+
+    if let Some(("config", config_matches)) = matches.subcommand() {
+        if let Some(("migrate", migrate_matches)) = config_matches.subcommand() {
+            migrate_config(&config_path, migrate_matches.get_flag("dry-run"));
+        }
+        return;
+    }
+
+    if matches.get_flag("check-config") {
+        let registry = ModuleRegistry::new();
+        let mut ok = true;
+
+        for path in Config::layer_paths(&config_path, profile) {
+            let report = Config::check(&path, &registry.names());
+            println!("Checking {}", report.path.display());
+
+            for error in &report.errors {
+                println!("error: {}", error);
+            }
+            for warning in &report.warnings {
+                println!("warning: {}", warning);
+            }
+
+            if report.is_ok() {
+                println!("Config OK ({} warnings)", report.warnings.len());
+            } else {
+                ok = false;
+            }
+        }
+
+        if !ok {
+            std::process::exit(1);
+        }
+        return;
+    }
+
     if matches.get_flag("generate-config") {
         let config = Config::default();
-        match config.save() {
+        match config.save_to(&config_path) {
             Ok(_) => println!("Default config file generated successfully!"),
             Err(e) => eprintln!("Error generating config file: {}", e),
         }
         return;
     }
 
-    let config = if matches.get_flag("no-config") {
+    let mut config = if matches.get_flag("no-config") {
         Config::default()
+    } else if let Some(path) = &custom_config {
+        Config::load_from(path, profile)
     } else {
-        Config::load()
+        Config::load(profile)
     };
 
+    for assignment in matches.get_many::<String>("set").into_iter().flatten() {
+        let result = match assignment.split_once('=') {
+            Some((key, value)) => config.set(key.trim(), value.trim()),
+            None => Err("expected KEY=VALUE".to_string()),
+        };
+        if let Err(e) = result {
+            eprintln!("Error: --set {}: {}", assignment, e);
+            std::process::exit(1);
+        }
+    }
+    if let Some(theme) = matches.get_one::<String>("theme") {
+        config.theme = Some(theme.clone());
+    }
+    if let Some(position) = matches.get_one::<String>("logo-position") {
+        config.logo_position = position.parse().expect("validated by clap");
+    }
+    for name in matches.get_many::<String>("show").into_iter().flatten() {
+        config.show_module(name);
+    }
+    for name in matches.get_many::<String>("hide").into_iter().flatten() {
+        config.hide_module(name);
+    }
+
     // Enable screenshot mode if flag is set
     let screenshot_mode = matches.get_flag("screenshot");
 
-    let system_info = SystemInfo::new();
-    let display = Display::new(config);
+    let registry = ModuleRegistry::new();
+    let modules = registry.enabled(&config);
+    let root = matches
+        .get_one::<String>("sysroot")
+        .map(String::as_str)
+        .unwrap_or("/");
+    let ctx = ProbeContext::new(
+        Arc::new(SystemRunner),
+        root,
+        Duration::from_millis(config.probe_timeout_ms),
+    );
+    let cache = ProbeCache::new(matches.get_flag("refresh")).for_root(Path::new(root));
+    let system_info = SystemInfo::new(&ctx, &modules, &config, &cache);
+
+    if matches.get_one::<String>("format").map(String::as_str) == Some("json") {
+        let export_info = if screenshot_mode {
+            DataRedactor::redact_system_info(&system_info, &config, &modules)
+        } else {
+            system_info
+        };
+        println!(
+            "{}",
+            JsonExporter::to_string_pretty(&export_info, &modules, screenshot_mode)
+        );
+        return;
+    }
 
-    display.show(&system_info, screenshot_mode);
+    let theme = Theme::for_config(&config, &Config::themes_dir(&config_path));
+    let display = Display::new(config, theme);
+
+    display.show(&system_info, &modules, screenshot_mode);
+}
+
+fn migrate_config(path: &Path, dry_run: bool) {
+    let contents = match std::fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) => {
+            eprintln!("Error reading config {}: {}", path.display(), e);
+            std::process::exit(1);
+        }
+    };
+
+    let Some(migration) = ConfigMigrator::migrate(&contents) else {
+        match ConfigMigrator::read_version(&contents) {
+            Some(version) if version > CONFIG_VERSION => println!(
+                "{} is at version {}, newer than this build ({}), nothing to migrate",
+                path.display(),
+                version,
+                CONFIG_VERSION
+            ),
+            Some(version) => println!(
+                "{} is already at version {}, nothing to migrate",
+                path.display(),
+                version
+            ),
+            None => {
+                eprintln!(
+                    "Error: {} is not valid TOML. Run `hxfetch --check-config` for details.",
+                    path.display()
+                );
+                std::process::exit(1);
+            }
+        }
+        return;
+    };
+
+    println!(
+        "Migrating {} from version {} to {}",
+        path.display(),
+        migration.from,
+        migration.to
+    );
+    print!("{}", migration.diff());
+
+    if dry_run {
+        return;
+    }
+
+    match migration.apply(path) {
+        Ok(backup) => println!("Config migrated, backup saved to {}", backup.display()),
+        Err(e) => {
+            eprintln!("Error writing migrated config: {}", e);
+            std::process::exit(1);
+        }
+    }
 }
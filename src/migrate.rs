@@ -0,0 +1,247 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use toml_edit::{Array, DocumentMut, Item, Key, Table, Value};
+
+/// Version written by this build. Bump it together with a new entry in
+/// `MIGRATIONS` whenever the meaning of an existing key changes.
+pub const CONFIG_VERSION: u32 = 3;
+
+/// Configs without a `version` key predate versioning
+const UNVERSIONED: u32 = 1;
+
+/// Upgrades the top-level table of a config by one version
+type MigrationStep = fn(&mut Table);
+
+/// Steps that upgrade a config from the version in the first field to the next
+const MIGRATIONS: &[(u32, MigrationStep)] = &[
+    (1, ConfigMigrator::v1_modules_list),
+    (2, ConfigMigrator::v2_theme_colors),
+];
+
+/// An upgraded config, ready to be written back
+pub struct Migration {
+    pub from: u32,
+    pub to: u32,
+    /// Whether any key changed; otherwise only `version` is bumped and the
+    /// file reads the same as before
+    pub keys_changed: bool,
+    pub original: String,
+    pub migrated: String,
+}
+
+impl Migration {
+    /// Line diff between the original and the migrated file
+    pub fn diff(&self) -> String {
+        ConfigMigrator::diff(&self.original, &self.migrated)
+    }
+
+    /// Writes the migrated config to `path`, keeping the original next to it
+    /// as `<file>.v<from>.bak`. Returns the backup path.
+    pub fn apply(&self, path: &Path) -> std::io::Result<PathBuf> {
+        let mut backup = path.as_os_str().to_owned();
+        backup.push(format!(".v{}.bak", self.from));
+        let backup = PathBuf::from(backup);
+
+        fs::write(&backup, &self.original)?;
+        fs::write(path, &self.migrated)?;
+        Ok(backup)
+    }
+}
+
+pub struct ConfigMigrator;
+
+impl ConfigMigrator {
+    /// Upgrades `contents` to `CONFIG_VERSION`, always writing the new
+    /// `version`. Returns `None` when the config is already current, from a
+    /// newer build, or is not valid TOML (parse errors are reported by the
+    /// regular loading path). Only the changed keys are touched, comments and
+    /// formatting are kept.
+    pub fn migrate(contents: &str) -> Option<Migration> {
+        let mut document: DocumentMut = contents.parse().ok()?;
+        let from = Self::version(document.as_table());
+        if from >= CONFIG_VERSION {
+            return None;
+        }
+
+        for (version, step) in MIGRATIONS {
+            if *version >= from {
+                step(document.as_table_mut());
+            }
+        }
+        let keys_changed = document.to_string() != contents;
+        Self::set_version(document.as_table_mut());
+
+        Some(Migration {
+            from,
+            to: CONFIG_VERSION,
+            keys_changed,
+            original: contents.to_string(),
+            migrated: document.to_string(),
+        })
+    }
+
+    /// The `version` of a config file, `None` if it is not valid TOML
+    pub fn read_version(contents: &str) -> Option<u32> {
+        let document: DocumentMut = contents.parse().ok()?;
+        Some(Self::version(document.as_table()))
+    }
+
+    fn version(table: &Table) -> u32 {
+        table
+            .get("version")
+            .and_then(Item::as_integer)
+            .and_then(|version| u32::try_from(version).ok())
+            .unwrap_or(UNVERSIONED)
+    }
+
+    /// Updates the `version` key in place, or adds it as the first key
+    fn set_version(table: &mut Table) {
+        let version = Value::from(i64::from(CONFIG_VERSION));
+        if let Some(value) = table.get_mut("version").and_then(Item::as_value_mut) {
+            let decor = value.decor().clone();
+            *value = version;
+            *value.decor_mut() = decor;
+            return;
+        }
+
+        let entries = Self::take_entries(table);
+        table.insert("version", Item::Value(version));
+        for (key, item) in entries {
+            table.insert_formatted(&key, item);
+        }
+    }
+
+    /// Removes all entries from `table`, keeping the comments attached to
+    /// their keys
+    fn take_entries(table: &mut Table) -> Vec<(Key, Item)> {
+        let keys: Vec<String> = table.iter().map(|(key, _)| key.to_string()).collect();
+        keys.iter()
+            .filter_map(|key| table.remove_entry(key))
+            .collect()
+    }
+
+    /// v1 had one `show_*` boolean per line of output (all defaulting to true)
+    /// plus `show_security` for the whole security block. v2 replaces them with
+    /// the ordered `modules` list.
+    fn v1_modules_list(table: &mut Table) {
+        let flags: Vec<(String, bool)> = table
+            .iter()
+            .filter_map(|(key, item)| {
+                let name = key.strip_prefix("show_")?;
+                Some((name.to_string(), item.as_bool().unwrap_or(true)))
+            })
+            .collect();
+        if flags.is_empty() {
+            return;
+        }
+
+        let shown = |name: &str| {
+            flags
+                .iter()
+                .find(|(flag, _)| flag == name)
+                .is_none_or(|(_, shown)| *shown)
+        };
+        let security = [
+            "firewall",
+            "selinux",
+            "ssh",
+            "fail2ban",
+            "secure_boot",
+            "apparmor",
+            "updates",
+            "encryption",
+        ];
+        let mut modules: Array = crate::config::Config::default_modules()
+            .into_iter()
+            .filter(|name| shown(name))
+            .filter(|name| shown("security") || !security.contains(&name.as_str()))
+            .collect();
+        for module in modules.iter_mut() {
+            module.decor_mut().set_prefix("\n    ");
+        }
+        modules.set_trailing_comma(true);
+        modules.set_trailing("\n");
+
+        // The list takes the place of the first flag, with its comments, so
+        // the file keeps its layout
+        let mut modules = (!table.contains_key("modules")).then_some(modules);
+        for (key, item) in Self::take_entries(table) {
+            if !key.get().starts_with("show_") {
+                table.insert_formatted(&key, item);
+            } else if let Some(modules) = modules.take() {
+                let modules_key = Key::new("modules").with_leaf_decor(key.leaf_decor().clone());
+                let mut modules = Value::Array(modules);
+                if let Some(flag) = item.as_value() {
+                    modules
+                        .decor_mut()
+                        .set_suffix(flag.decor().suffix().cloned().unwrap_or_default());
+                }
+                table.insert_formatted(&modules_key, Item::Value(modules));
+            }
+        }
+    }
+
+    /// Up to v2 every color key was written out with its default value. In v3
+    /// the colors come from the theme and the keys only override it, so a
+    /// generated file, with all five keys at their defaults, loses them to let
+    /// `theme` take effect. Files with only some of the keys set them on
+    /// purpose and are left alone.
+    fn v2_theme_colors(table: &mut Table) {
+        let defaults = [
+            ("logo_color", "cyan"),
+            ("accent_color", "blue"),
+            ("label_color", "cyan"),
+            ("value_color", "white"),
+            ("separator_color", "blue"),
+        ];
+
+        let generated = defaults.iter().all(|(key, default)| {
+            table
+                .get(key)
+                .and_then(Item::as_str)
+                .is_some_and(|color| color.eq_ignore_ascii_case(default))
+        });
+        if generated {
+            for (key, _) in defaults {
+                table.remove(key);
+            }
+        }
+    }
+
+    /// Minimal unified-style diff based on the longest common subsequence of
+    /// lines. Config files are small, so the quadratic table is fine.
+    fn diff(old: &str, new: &str) -> String {
+        let old: Vec<&str> = old.lines().collect();
+        let new: Vec<&str> = new.lines().collect();
+
+        // common[i][j] = length of the LCS of old[i..] and new[j..]
+        let mut common = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+        for i in (0..old.len()).rev() {
+            for j in (0..new.len()).rev() {
+                common[i][j] = if old[i] == new[j] {
+                    common[i + 1][j + 1] + 1
+                } else {
+                    common[i + 1][j].max(common[i][j + 1])
+                };
+            }
+        }
+
+        let mut output = String::new();
+        let (mut i, mut j) = (0, 0);
+        while i < old.len() || j < new.len() {
+            if i < old.len() && j < new.len() && old[i] == new[j] {
+                output.push_str(&format!(" {}\n", old[i]));
+                i += 1;
+                j += 1;
+            } else if j < new.len() && (i == old.len() || common[i][j + 1] >= common[i + 1][j]) {
+                output.push_str(&format!("+{}\n", new[j]));
+                j += 1;
+            } else {
+                output.push_str(&format!("-{}\n", old[i]));
+                i += 1;
+            }
+        }
+
+        output
+    }
+}
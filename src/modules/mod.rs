@@ -0,0 +1,252 @@
+mod security;
+mod system;
+
+use crate::config::Config;
+use crate::probe::ProbeContext;
+use crate::sysinfo::SystemInfo;
+use serde_json::Value;
+
+/// Where a module's lines are placed in the output.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Section {
+    /// Regular system information below the header
+    System,
+    /// Grouped under the "Security:" heading
+    Security,
+    /// Drawn last, after the screenshot notice
+    Footer,
+}
+
+/// A single rendered output line.
+pub enum Line {
+    /// A `Label: value` pair, colored by the display
+    Info { label: String, value: String },
+    /// A `Label: value` pair for something with a capacity, followed by a
+    /// usage bar when `usage_bars` is on
+    Usage {
+        label: String,
+        value: String,
+        used: u64,
+        total: u64,
+    },
+    /// A `Label: value` pair for a temperature, converted to the configured
+    /// unit and colored as it nears `critical`
+    Temperature {
+        label: String,
+        celsius: f64,
+        critical: Option<f64>,
+    },
+    /// A row of color blocks in the theme's palette
+    Palette,
+}
+
+impl Line {
+    pub fn info(label: &str, value: impl Into<String>) -> Self {
+        Line::Info {
+            label: label.to_string(),
+            value: value.into(),
+        }
+    }
+
+    pub fn usage(label: &str, value: impl Into<String>, used: u64, total: u64) -> Self {
+        Line::Usage {
+            label: label.to_string(),
+            value: value.into(),
+            used,
+            total,
+        }
+    }
+}
+
+/// One piece of system information: how to probe it, how to redact it for
+/// screenshots and how to turn it into output lines.
+pub trait Module: Send + Sync {
+    /// Name used in the `modules` list of the config
+    fn name(&self) -> &'static str;
+
+    /// Dotted path of the value this module fills in the serialized `SystemInfo`,
+    /// used to select keys for JSON output
+    fn field(&self) -> Option<&'static str> {
+        None
+    }
+
+    fn section(&self) -> Section {
+        Section::System
+    }
+
+    /// Modules that are left out completely in screenshot mode
+    fn sensitive(&self) -> bool {
+        false
+    }
+
+    /// Files whose modification time invalidates a cached result, such as a
+    /// package database. Only used for modules with a `cache_ttl` entry.
+    fn cache_inputs(&self) -> &'static [&'static str] {
+        &[]
+    }
+
+    /// Probes the system and returns the value for `field`. Modules are
+    /// collected concurrently, so the result is handed back as JSON and merged
+    /// into `SystemInfo` afterwards.
+    fn collect(&self, ctx: &ProbeContext) -> Value;
+
+    /// Applies config settings that narrow down the collected value, such as
+    /// filters. Runs after collection, so cached results do not depend on them.
+    fn apply_config(&self, _info: &mut SystemInfo, _config: &Config) {}
+
+    /// Removes identifying details from `info` for screenshot mode
+    fn redact(&self, _info: &mut SystemInfo, _config: &Config) {}
+
+    /// Produces the output lines, or nothing when there is nothing worth showing
+    fn render(&self, info: &SystemInfo, screenshot_mode: bool) -> Vec<Line>;
+}
+
+/// JSON pointer for a dotted module field such as `security.ssh_status`
+pub fn field_pointer(field: &str) -> String {
+    format!("/{}", field.replace('.', "/"))
+}
+
+/// The modules that probe, filter and redact their field: the first of each
+/// set sharing a field (such as `storage` and `disks`), so the probe runs once
+/// and the others render from the same value. Modules without a field are
+/// all kept.
+pub fn field_owners<'a>(modules: &[&'a dyn Module]) -> Vec<&'a dyn Module> {
+    let mut seen = Vec::new();
+    modules
+        .iter()
+        .copied()
+        .filter(|module| match module.field() {
+            Some(field) if seen.contains(&field) => false,
+            Some(field) => {
+                seen.push(field);
+                true
+            }
+            None => true,
+        })
+        .collect()
+}
+
+/// Whether a layout lookup such as `os.name`, `gpu` or `security.ssh_status`
+/// reads the value of `module`
+pub fn references(module: &dyn Module, name: &str) -> bool {
+    if module.name() == name {
+        return true;
+    }
+    module.field().is_some_and(|field| {
+        name == field
+            || name
+                .strip_prefix(field)
+                .is_some_and(|rest| rest.starts_with('.'))
+    })
+}
+
+/// Shell-style pattern match where `*` matches any run of characters
+/// (including `/`) and `?` a single one
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    // Position after the last `*` and the text position it was tried at
+    let mut backtrack = None;
+
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p + 1, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match backtrack {
+                Some((star, tried)) => {
+                    p = star;
+                    t = tried + 1;
+                    backtrack = Some((star, tried + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+pub struct ModuleRegistry {
+    modules: Vec<Box<dyn Module>>,
+}
+
+impl ModuleRegistry {
+    /// Registry with every built-in module, in the default display order
+    pub fn new() -> Self {
+        Self {
+            modules: vec![
+                Box::new(system::OsModule),
+                Box::new(system::KernelModule),
+                Box::new(system::UptimeModule),
+                Box::new(system::CpuModule),
+                Box::new(system::MemoryModule),
+                Box::new(system::SwapModule),
+                Box::new(system::StorageModule),
+                Box::new(system::DisksModule),
+                Box::new(system::ShellModule),
+                Box::new(system::DesktopModule),
+                Box::new(system::TerminalModule),
+                Box::new(system::GpuModule),
+                Box::new(system::TemperatureModule),
+                Box::new(system::FansModule),
+                Box::new(system::NetworkModule),
+                Box::new(system::InterfacesModule),
+                Box::new(system::PackagesModule),
+                Box::new(security::FirewallModule),
+                Box::new(security::SelinuxModule),
+                Box::new(security::SshModule),
+                Box::new(security::Fail2banModule),
+                Box::new(security::SecureBootModule),
+                Box::new(security::ApparmorModule),
+                Box::new(security::UpdatesModule),
+                Box::new(security::EncryptionModule),
+                Box::new(system::ColorsModule),
+            ],
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&dyn Module> {
+        self.modules
+            .iter()
+            .find(|module| module.name() == name)
+            .map(|module| module.as_ref())
+    }
+
+    pub fn names(&self) -> Vec<&'static str> {
+        self.modules.iter().map(|module| module.name()).collect()
+    }
+
+    /// Modules listed in the config, in the configured order. With a custom
+    /// layout, modules it doesn't reference are skipped as well.
+    /// Unknown names are reported and skipped.
+    pub fn enabled(&self, config: &Config) -> Vec<&dyn Module> {
+        let mut enabled: Vec<&dyn Module> = Vec::new();
+        let layout_fields = config.layout_fields();
+
+        for name in &config.modules {
+            match self.get(name) {
+                Some(module) => {
+                    let referenced = layout_fields.is_empty()
+                        || layout_fields.iter().any(|field| references(module, field));
+                    if referenced && !enabled.iter().any(|m| m.name() == module.name()) {
+                        enabled.push(module);
+                    }
+                }
+                None => eprintln!(
+                    "Warning: Unknown module '{}' (available: {})",
+                    name,
+                    self.names().join(", ")
+                ),
+            }
+        }
+
+        enabled
+    }
+}
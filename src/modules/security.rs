@@ -0,0 +1,251 @@
+use super::{Line, Module, Section};
+use crate::probe::ProbeContext;
+use crate::security::SecurityInfo;
+use crate::sysinfo::SystemInfo;
+use serde_json::{json, Value};
+
+pub struct FirewallModule;
+
+impl Module for FirewallModule {
+    fn name(&self) -> &'static str {
+        "firewall"
+    }
+
+    fn field(&self) -> Option<&'static str> {
+        Some("security.firewall_status")
+    }
+
+    fn section(&self) -> Section {
+        Section::Security
+    }
+
+    fn sensitive(&self) -> bool {
+        true
+    }
+
+    fn collect(&self, ctx: &ProbeContext) -> Value {
+        json!(SecurityInfo::get_firewall_status(ctx))
+    }
+
+    fn render(&self, info: &SystemInfo, _screenshot_mode: bool) -> Vec<Line> {
+        if info.security.firewall_status == "None detected" {
+            return Vec::new();
+        }
+        vec![Line::info("Firewall", &info.security.firewall_status)]
+    }
+}
+
+pub struct SelinuxModule;
+
+impl Module for SelinuxModule {
+    fn name(&self) -> &'static str {
+        "selinux"
+    }
+
+    fn field(&self) -> Option<&'static str> {
+        Some("security.selinux_status")
+    }
+
+    fn section(&self) -> Section {
+        Section::Security
+    }
+
+    fn sensitive(&self) -> bool {
+        true
+    }
+
+    fn collect(&self, ctx: &ProbeContext) -> Value {
+        json!(SecurityInfo::get_selinux_status(ctx))
+    }
+
+    fn render(&self, info: &SystemInfo, _screenshot_mode: bool) -> Vec<Line> {
+        if info.security.selinux_status == "Not available" {
+            return Vec::new();
+        }
+        vec![Line::info("SELinux", &info.security.selinux_status)]
+    }
+}
+
+pub struct SshModule;
+
+impl Module for SshModule {
+    fn name(&self) -> &'static str {
+        "ssh"
+    }
+
+    fn field(&self) -> Option<&'static str> {
+        Some("security.ssh_status")
+    }
+
+    fn section(&self) -> Section {
+        Section::Security
+    }
+
+    fn sensitive(&self) -> bool {
+        true
+    }
+
+    fn collect(&self, ctx: &ProbeContext) -> Value {
+        json!(SecurityInfo::get_ssh_status(ctx))
+    }
+
+    fn render(&self, info: &SystemInfo, _screenshot_mode: bool) -> Vec<Line> {
+        vec![Line::info("SSH", &info.security.ssh_status)]
+    }
+}
+
+pub struct Fail2banModule;
+
+impl Module for Fail2banModule {
+    fn name(&self) -> &'static str {
+        "fail2ban"
+    }
+
+    fn field(&self) -> Option<&'static str> {
+        Some("security.fail2ban_status")
+    }
+
+    fn section(&self) -> Section {
+        Section::Security
+    }
+
+    fn sensitive(&self) -> bool {
+        true
+    }
+
+    fn collect(&self, ctx: &ProbeContext) -> Value {
+        json!(SecurityInfo::get_fail2ban_status(ctx))
+    }
+
+    fn render(&self, info: &SystemInfo, _screenshot_mode: bool) -> Vec<Line> {
+        if info.security.fail2ban_status == "Not installed" {
+            return Vec::new();
+        }
+        vec![Line::info("Fail2Ban", &info.security.fail2ban_status)]
+    }
+}
+
+pub struct SecureBootModule;
+
+impl Module for SecureBootModule {
+    fn name(&self) -> &'static str {
+        "secure_boot"
+    }
+
+    fn field(&self) -> Option<&'static str> {
+        Some("security.secure_boot")
+    }
+
+    fn section(&self) -> Section {
+        Section::Security
+    }
+
+    fn sensitive(&self) -> bool {
+        true
+    }
+
+    fn collect(&self, ctx: &ProbeContext) -> Value {
+        json!(SecurityInfo::get_secure_boot_status(ctx))
+    }
+
+    fn render(&self, info: &SystemInfo, _screenshot_mode: bool) -> Vec<Line> {
+        if info.security.secure_boot == "Unknown" {
+            return Vec::new();
+        }
+        vec![Line::info("Secure Boot", &info.security.secure_boot)]
+    }
+}
+
+pub struct ApparmorModule;
+
+impl Module for ApparmorModule {
+    fn name(&self) -> &'static str {
+        "apparmor"
+    }
+
+    fn field(&self) -> Option<&'static str> {
+        Some("security.apparmor_status")
+    }
+
+    fn section(&self) -> Section {
+        Section::Security
+    }
+
+    fn collect(&self, ctx: &ProbeContext) -> Value {
+        json!(SecurityInfo::get_apparmor_status(ctx))
+    }
+
+    fn render(&self, info: &SystemInfo, _screenshot_mode: bool) -> Vec<Line> {
+        if info.security.apparmor_status == "Not available" {
+            return Vec::new();
+        }
+        vec![Line::info("AppArmor", &info.security.apparmor_status)]
+    }
+}
+
+pub struct UpdatesModule;
+
+impl Module for UpdatesModule {
+    fn name(&self) -> &'static str {
+        "updates"
+    }
+
+    fn field(&self) -> Option<&'static str> {
+        Some("security.package_updates")
+    }
+
+    fn section(&self) -> Section {
+        Section::Security
+    }
+
+    fn cache_inputs(&self) -> &'static [&'static str] {
+        &[
+            "/var/lib/dpkg/status",
+            "/var/lib/apt/lists",
+            "/var/lib/rpm",
+            "/var/cache/dnf",
+            "/var/cache/zypp/solv",
+            "/var/lib/pacman/local",
+            "/var/lib/pacman/sync",
+            "/lib/apk/db/installed",
+            "/var/cache/apk",
+            "/var/db/xbps",
+            "/var/lib/flatpak/repo/refs/remotes",
+        ]
+    }
+
+    fn collect(&self, ctx: &ProbeContext) -> Value {
+        json!(SecurityInfo::get_package_updates(ctx))
+    }
+
+    fn render(&self, info: &SystemInfo, _screenshot_mode: bool) -> Vec<Line> {
+        vec![Line::info("Updates", &info.security.package_updates)]
+    }
+}
+
+pub struct EncryptionModule;
+
+impl Module for EncryptionModule {
+    fn name(&self) -> &'static str {
+        "encryption"
+    }
+
+    fn field(&self) -> Option<&'static str> {
+        Some("security.disk_encryption")
+    }
+
+    fn section(&self) -> Section {
+        Section::Security
+    }
+
+    fn collect(&self, ctx: &ProbeContext) -> Value {
+        json!(SecurityInfo::get_disk_encryption_status(ctx))
+    }
+
+    fn render(&self, info: &SystemInfo, _screenshot_mode: bool) -> Vec<Line> {
+        if info.security.disk_encryption == "None detected" {
+            return Vec::new();
+        }
+        vec![Line::info("Encryption", &info.security.disk_encryption)]
+    }
+}
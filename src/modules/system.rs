@@ -0,0 +1,534 @@
+use super::{Line, Module, Section};
+use crate::config::Config;
+use crate::display::Display;
+use crate::probe::ProbeContext;
+use crate::redactor::DataRedactor;
+use crate::sysinfo::{SystemInfo, UsageBucket};
+use serde_json::{json, Value};
+
+pub struct OsModule;
+
+impl Module for OsModule {
+    fn name(&self) -> &'static str {
+        "os"
+    }
+
+    fn field(&self) -> Option<&'static str> {
+        Some("os")
+    }
+
+    fn collect(&self, ctx: &ProbeContext) -> Value {
+        json!(SystemInfo::get_os_info(ctx))
+    }
+
+    fn render(&self, info: &SystemInfo, _screenshot_mode: bool) -> Vec<Line> {
+        vec![Line::info("OS", &info.os.pretty_name)]
+    }
+}
+
+pub struct KernelModule;
+
+impl Module for KernelModule {
+    fn name(&self) -> &'static str {
+        "kernel"
+    }
+
+    fn field(&self) -> Option<&'static str> {
+        Some("kernel")
+    }
+
+    fn collect(&self, ctx: &ProbeContext) -> Value {
+        json!(SystemInfo::get_kernel_version(ctx))
+    }
+
+    fn render(&self, info: &SystemInfo, _screenshot_mode: bool) -> Vec<Line> {
+        vec![Line::info("Kernel", &info.kernel)]
+    }
+}
+
+pub struct UptimeModule;
+
+impl Module for UptimeModule {
+    fn name(&self) -> &'static str {
+        "uptime"
+    }
+
+    fn field(&self) -> Option<&'static str> {
+        Some("uptime")
+    }
+
+    fn collect(&self, _ctx: &ProbeContext) -> Value {
+        json!(SystemInfo::get_uptime())
+    }
+
+    fn render(&self, info: &SystemInfo, _screenshot_mode: bool) -> Vec<Line> {
+        vec![Line::info("Uptime", Display::format_uptime(info.uptime))]
+    }
+}
+
+pub struct CpuModule;
+
+impl Module for CpuModule {
+    fn name(&self) -> &'static str {
+        "cpu"
+    }
+
+    fn field(&self) -> Option<&'static str> {
+        Some("cpu")
+    }
+
+    fn collect(&self, _ctx: &ProbeContext) -> Value {
+        json!(SystemInfo::get_cpu_info())
+    }
+
+    fn redact(&self, info: &mut SystemInfo, config: &Config) {
+        info.cpu = DataRedactor::redact_cpu(&info.cpu, config.redact_cpu_model);
+    }
+
+    fn render(&self, info: &SystemInfo, _screenshot_mode: bool) -> Vec<Line> {
+        vec![Line::info("CPU", Display::format_cpu(&info.cpu))]
+    }
+}
+
+pub struct MemoryModule;
+
+impl Module for MemoryModule {
+    fn name(&self) -> &'static str {
+        "memory"
+    }
+
+    fn field(&self) -> Option<&'static str> {
+        Some("memory")
+    }
+
+    fn collect(&self, _ctx: &ProbeContext) -> Value {
+        json!(SystemInfo::get_memory_info())
+    }
+
+    fn redact(&self, info: &mut SystemInfo, _config: &Config) {
+        info.memory = DataRedactor::redact_memory(&info.memory);
+    }
+
+    fn render(&self, info: &SystemInfo, _screenshot_mode: bool) -> Vec<Line> {
+        let memory = &info.memory;
+        let (used, total) = UsageBucket::ratio(memory.used, memory.total, memory.bucket.as_ref());
+        vec![Line::usage(
+            "Memory",
+            Display::format_memory(memory),
+            used,
+            total,
+        )]
+    }
+}
+
+pub struct SwapModule;
+
+impl Module for SwapModule {
+    fn name(&self) -> &'static str {
+        "swap"
+    }
+
+    fn field(&self) -> Option<&'static str> {
+        Some("swap")
+    }
+
+    fn collect(&self, _ctx: &ProbeContext) -> Value {
+        json!(SystemInfo::get_swap_info())
+    }
+
+    fn redact(&self, info: &mut SystemInfo, _config: &Config) {
+        info.swap = DataRedactor::redact_memory(&info.swap);
+    }
+
+    fn render(&self, info: &SystemInfo, _screenshot_mode: bool) -> Vec<Line> {
+        // Nothing to show on machines without swap
+        let swap = &info.swap;
+        if swap.total == Some(0) {
+            return Vec::new();
+        }
+        let (used, total) = UsageBucket::ratio(swap.used, swap.total, swap.bucket.as_ref());
+        vec![Line::usage(
+            "Swap",
+            Display::format_memory(swap),
+            used,
+            total,
+        )]
+    }
+}
+
+pub struct StorageModule;
+
+impl Module for StorageModule {
+    fn name(&self) -> &'static str {
+        "storage"
+    }
+
+    fn field(&self) -> Option<&'static str> {
+        Some("storage")
+    }
+
+    fn collect(&self, ctx: &ProbeContext) -> Value {
+        json!(SystemInfo::get_storage_info(ctx))
+    }
+
+    fn apply_config(&self, info: &mut SystemInfo, config: &Config) {
+        SystemInfo::filter_storage(&mut info.storage, config);
+    }
+
+    fn redact(&self, info: &mut SystemInfo, _config: &Config) {
+        info.storage = DataRedactor::redact_storage(&info.storage);
+    }
+
+    fn render(&self, info: &SystemInfo, _screenshot_mode: bool) -> Vec<Line> {
+        let value = Display::format_storage(&info.storage);
+        match &info.storage {
+            Some(storage) => {
+                let (used, total) =
+                    UsageBucket::ratio(storage.used, storage.total, storage.bucket.as_ref());
+                vec![Line::usage("Storage", value, used, total)]
+            }
+            None => vec![Line::info("Storage", value)],
+        }
+    }
+}
+
+/// One line per mounted filesystem with its type and read-only flag, instead
+/// of the single storage total
+pub struct DisksModule;
+
+impl Module for DisksModule {
+    fn name(&self) -> &'static str {
+        "disks"
+    }
+
+    fn field(&self) -> Option<&'static str> {
+        Some("storage")
+    }
+
+    fn collect(&self, ctx: &ProbeContext) -> Value {
+        json!(SystemInfo::get_storage_info(ctx))
+    }
+
+    fn apply_config(&self, info: &mut SystemInfo, config: &Config) {
+        SystemInfo::filter_storage(&mut info.storage, config);
+    }
+
+    fn redact(&self, info: &mut SystemInfo, _config: &Config) {
+        info.storage = DataRedactor::redact_storage(&info.storage);
+    }
+
+    fn render(&self, info: &SystemInfo, _screenshot_mode: bool) -> Vec<Line> {
+        let Some(storage) = &info.storage else {
+            return Vec::new();
+        };
+        storage
+            .disks
+            .iter()
+            .map(|disk| {
+                let (used, total) = UsageBucket::ratio(disk.used, disk.total, disk.bucket.as_ref());
+                Line::usage(
+                    &format!("Disk ({})", disk.mount_point),
+                    Display::format_disk(disk),
+                    used,
+                    total,
+                )
+            })
+            .collect()
+    }
+}
+
+pub struct ShellModule;
+
+impl Module for ShellModule {
+    fn name(&self) -> &'static str {
+        "shell"
+    }
+
+    fn field(&self) -> Option<&'static str> {
+        Some("shell")
+    }
+
+    fn collect(&self, _ctx: &ProbeContext) -> Value {
+        json!(SystemInfo::get_shell())
+    }
+
+    fn render(&self, info: &SystemInfo, _screenshot_mode: bool) -> Vec<Line> {
+        vec![Line::info("Shell", &info.shell)]
+    }
+}
+
+pub struct DesktopModule;
+
+impl Module for DesktopModule {
+    fn name(&self) -> &'static str {
+        "desktop"
+    }
+
+    fn field(&self) -> Option<&'static str> {
+        Some("desktop")
+    }
+
+    fn collect(&self, _ctx: &ProbeContext) -> Value {
+        json!(SystemInfo::get_desktop_environment())
+    }
+
+    fn render(&self, info: &SystemInfo, _screenshot_mode: bool) -> Vec<Line> {
+        if info.desktop == "Unknown" {
+            return Vec::new();
+        }
+        vec![Line::info("DE", &info.desktop)]
+    }
+}
+
+pub struct TerminalModule;
+
+impl Module for TerminalModule {
+    fn name(&self) -> &'static str {
+        "terminal"
+    }
+
+    fn field(&self) -> Option<&'static str> {
+        Some("terminal")
+    }
+
+    fn collect(&self, _ctx: &ProbeContext) -> Value {
+        json!(SystemInfo::get_terminal())
+    }
+
+    fn render(&self, info: &SystemInfo, _screenshot_mode: bool) -> Vec<Line> {
+        vec![Line::info("Terminal", &info.terminal)]
+    }
+}
+
+pub struct GpuModule;
+
+impl Module for GpuModule {
+    fn name(&self) -> &'static str {
+        "gpu"
+    }
+
+    fn field(&self) -> Option<&'static str> {
+        Some("gpus")
+    }
+
+    fn collect(&self, ctx: &ProbeContext) -> Value {
+        json!(SystemInfo::get_gpu_info(ctx))
+    }
+
+    fn redact(&self, info: &mut SystemInfo, _config: &Config) {
+        for gpu in &mut info.gpus {
+            gpu.name = DataRedactor::redact_gpu(&gpu.name);
+        }
+    }
+
+    fn render(&self, info: &SystemInfo, _screenshot_mode: bool) -> Vec<Line> {
+        info.gpus
+            .iter()
+            .map(|gpu| Line::info("GPU", Display::format_gpu(gpu)))
+            .collect()
+    }
+}
+
+pub struct TemperatureModule;
+
+impl Module for TemperatureModule {
+    fn name(&self) -> &'static str {
+        "temperature"
+    }
+
+    fn field(&self) -> Option<&'static str> {
+        Some("sensors")
+    }
+
+    fn collect(&self, ctx: &ProbeContext) -> Value {
+        json!(SystemInfo::get_sensor_info(ctx))
+    }
+
+    fn apply_config(&self, info: &mut SystemInfo, config: &Config) {
+        SystemInfo::filter_sensors(&mut info.sensors, config);
+    }
+
+    fn render(&self, info: &SystemInfo, _screenshot_mode: bool) -> Vec<Line> {
+        info.sensors
+            .temperatures
+            .iter()
+            .map(|sensor| Line::Temperature {
+                label: format!("Temperature ({})", sensor.name()),
+                celsius: sensor.celsius,
+                critical: sensor.critical,
+            })
+            .collect()
+    }
+}
+
+pub struct FansModule;
+
+impl Module for FansModule {
+    fn name(&self) -> &'static str {
+        "fans"
+    }
+
+    fn field(&self) -> Option<&'static str> {
+        Some("sensors")
+    }
+
+    fn collect(&self, ctx: &ProbeContext) -> Value {
+        json!(SystemInfo::get_sensor_info(ctx))
+    }
+
+    fn apply_config(&self, info: &mut SystemInfo, config: &Config) {
+        SystemInfo::filter_sensors(&mut info.sensors, config);
+    }
+
+    fn render(&self, info: &SystemInfo, _screenshot_mode: bool) -> Vec<Line> {
+        info.sensors
+            .fans
+            .iter()
+            .map(|fan| Line::info(&format!("Fan ({})", fan.name()), format!("{} RPM", fan.rpm)))
+            .collect()
+    }
+}
+
+pub struct NetworkModule;
+
+impl Module for NetworkModule {
+    fn name(&self) -> &'static str {
+        "network"
+    }
+
+    fn field(&self) -> Option<&'static str> {
+        Some("network")
+    }
+
+    fn collect(&self, ctx: &ProbeContext) -> Value {
+        json!(SystemInfo::get_network_info(ctx))
+    }
+
+    fn apply_config(&self, info: &mut SystemInfo, config: &Config) {
+        SystemInfo::filter_network(&mut info.network, config);
+    }
+
+    fn redact(&self, info: &mut SystemInfo, _config: &Config) {
+        info.network = DataRedactor::redact_network(&info.network);
+    }
+
+    fn render(&self, info: &SystemInfo, _screenshot_mode: bool) -> Vec<Line> {
+        match &info.network {
+            Some(network) => vec![Line::info("Network", Display::format_network(network))],
+            None => Vec::new(),
+        }
+    }
+}
+
+/// One line per network interface, with its addresses and link details
+pub struct InterfacesModule;
+
+impl Module for InterfacesModule {
+    fn name(&self) -> &'static str {
+        "interfaces"
+    }
+
+    fn field(&self) -> Option<&'static str> {
+        Some("network")
+    }
+
+    fn collect(&self, ctx: &ProbeContext) -> Value {
+        json!(SystemInfo::get_network_info(ctx))
+    }
+
+    fn apply_config(&self, info: &mut SystemInfo, config: &Config) {
+        SystemInfo::filter_network(&mut info.network, config);
+    }
+
+    fn redact(&self, info: &mut SystemInfo, _config: &Config) {
+        info.network = DataRedactor::redact_network(&info.network);
+    }
+
+    fn render(&self, info: &SystemInfo, _screenshot_mode: bool) -> Vec<Line> {
+        let Some(network) = &info.network else {
+            return Vec::new();
+        };
+        network
+            .interfaces
+            .iter()
+            .map(|interface| {
+                Line::info(
+                    &format!("Interface ({})", interface.name),
+                    Display::format_interface(interface),
+                )
+            })
+            .collect()
+    }
+}
+
+pub struct PackagesModule;
+
+impl Module for PackagesModule {
+    fn name(&self) -> &'static str {
+        "packages"
+    }
+
+    fn field(&self) -> Option<&'static str> {
+        Some("packages")
+    }
+
+    fn cache_inputs(&self) -> &'static [&'static str] {
+        &[
+            "/var/lib/dpkg/status",
+            "/var/lib/rpm",
+            "/usr/lib/sysimage/rpm",
+            "/var/lib/pacman/local",
+            "/lib/apk/db/installed",
+            "/var/db/xbps",
+            "/var/db/pkg",
+            "/var/db/pkg/local.sqlite",
+            "/nix/var/nix/profiles",
+            "/var/lib/flatpak/app",
+            "/var/lib/flatpak/runtime",
+            "/var/lib/snapd/snaps",
+            "/home/linuxbrew/.linuxbrew/Cellar",
+            "/opt/homebrew/Cellar",
+            "/usr/local/Cellar",
+            "~/.nix-profile",
+            "~/.local/state/nix/profiles",
+            "~/.local/share/flatpak/app",
+            "~/.local/share/flatpak/runtime",
+            "~/.cargo/.crates2.json",
+            "~/.local/lib/python*/site-packages",
+        ]
+    }
+
+    fn collect(&self, ctx: &ProbeContext) -> Value {
+        json!(SystemInfo::get_package_count(ctx))
+    }
+
+    fn render(&self, info: &SystemInfo, _screenshot_mode: bool) -> Vec<Line> {
+        if info.packages.is_empty() {
+            return Vec::new();
+        }
+        vec![Line::info(
+            "Packages",
+            Display::format_packages(&info.packages),
+        )]
+    }
+}
+
+pub struct ColorsModule;
+
+impl Module for ColorsModule {
+    fn name(&self) -> &'static str {
+        "colors"
+    }
+
+    fn section(&self) -> Section {
+        Section::Footer
+    }
+
+    fn collect(&self, _ctx: &ProbeContext) -> Value {
+        Value::Null
+    }
+
+    fn render(&self, _info: &SystemInfo, _screenshot_mode: bool) -> Vec<Line> {
+        vec![Line::Palette]
+    }
+}
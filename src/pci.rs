@@ -0,0 +1,133 @@
+use crate::probe::ProbeContext;
+
+/// Built-in subset of the PCI ID database, used when the system has none
+const EMBEDDED_IDS: &str = include_str!("../data/pci.ids");
+
+/// Where distributions install `pci.ids` (hwdata, pciutils)
+const DATABASE_PATHS: &[&str] = &[
+    "/usr/share/hwdata/pci.ids",
+    "/usr/share/misc/pci.ids",
+    "/usr/share/pci.ids",
+];
+
+/// Corporate suffixes dropped from vendor names without a short form
+const VENDOR_SUFFIXES: &[&str] = &[
+    " Corporation",
+    " Corp.",
+    ", Inc.",
+    " Inc.",
+    " Co., Ltd.",
+    " Ltd.",
+    " GmbH",
+];
+
+/// Vendor and device names from the PCI ID database
+pub struct PciIds {
+    database: Option<String>,
+}
+
+impl PciIds {
+    /// Reads the system's `pci.ids`, if one is installed
+    pub fn load(ctx: &ProbeContext) -> Self {
+        Self {
+            database: DATABASE_PATHS
+                .iter()
+                .find_map(|path| ctx.read_to_string(path).ok()),
+        }
+    }
+
+    /// Display name such as "NVIDIA GeForce RTX 3070" and the short vendor
+    /// name. IDs missing from the system database are looked up in the
+    /// embedded subset, unknown ones are shown as hex.
+    pub fn name(&self, vendor: u16, device: u16) -> (String, String) {
+        let (mut vendor_name, mut device_name) = self
+            .database
+            .as_deref()
+            .map(|database| Self::lookup(database, vendor, device))
+            .unwrap_or_default();
+        if vendor_name.is_none() || device_name.is_none() {
+            let (embedded_vendor, embedded_device) = Self::lookup(EMBEDDED_IDS, vendor, device);
+            vendor_name = vendor_name.or(embedded_vendor);
+            device_name = device_name.or(embedded_device);
+        }
+
+        let vendor_name = vendor_name
+            .map(|name| Self::short_vendor(&name))
+            .unwrap_or_else(|| format!("{:04x}", vendor));
+        let name = match device_name {
+            Some(device_name) => {
+                let model = Self::model(&device_name);
+                if model.starts_with(&vendor_name) {
+                    model.to_string()
+                } else {
+                    format!("{} {}", vendor_name, model)
+                }
+            }
+            None => format!("{} device {:04x}", vendor_name, device),
+        };
+
+        (name, vendor_name)
+    }
+
+    /// Vendor and device names for `vendor:device` in a database in the
+    /// `pci.ids` format
+    pub fn lookup(database: &str, vendor: u16, device: u16) -> (Option<String>, Option<String>) {
+        let vendor_id = format!("{:04x}", vendor);
+        let device_id = format!("{:04x}", device);
+        let mut vendor_name = None;
+
+        for line in database.lines() {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            match line.strip_prefix('\t') {
+                // Device of the vendor we found; subsystems have two tabs
+                Some(entry) if vendor_name.is_some() && !entry.starts_with('\t') => {
+                    if let Some(name) = Self::entry_name(entry, &device_id) {
+                        return (vendor_name, Some(name));
+                    }
+                }
+                Some(_) => {}
+                // The next vendor or the class section
+                None if vendor_name.is_some() => break,
+                None => vendor_name = Self::entry_name(line, &vendor_id),
+            }
+        }
+
+        (vendor_name, None)
+    }
+
+    /// Name of an `id  name` entry if it has the given ID
+    fn entry_name(entry: &str, id: &str) -> Option<String> {
+        let name = entry.strip_prefix(id)?.strip_prefix("  ")?;
+        Some(name.trim().to_string())
+    }
+
+    /// "Advanced Micro Devices, Inc. [AMD/ATI]" becomes "AMD",
+    /// "Intel Corporation" becomes "Intel"
+    fn short_vendor(name: &str) -> String {
+        if let Some(short) = Self::bracketed(name) {
+            return short.split('/').next().unwrap_or(short).to_string();
+        }
+
+        let mut name = name;
+        for suffix in VENDOR_SUFFIXES {
+            name = name.strip_suffix(suffix).unwrap_or(name);
+        }
+        name.to_string()
+    }
+
+    /// The marketing name of a device, "GA104 [GeForce RTX 3070]" becomes
+    /// "GeForce RTX 3070"
+    fn model(name: &str) -> &str {
+        Self::bracketed(name).unwrap_or(name)
+    }
+
+    /// Text in the last pair of brackets
+    fn bracketed(name: &str) -> Option<&str> {
+        let inner = name.strip_suffix(']')?;
+        let start = inner.rfind('[')?;
+        Some(&inner[start + 1..]).filter(|inner| !inner.is_empty())
+    }
+}
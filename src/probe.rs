@@ -0,0 +1,150 @@
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, ExitStatus, Output, Stdio};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How often a running command is polled for completion
+const POLL_INTERVAL: Duration = Duration::from_millis(5);
+
+/// Executes external commands on behalf of the probes. The real system uses
+/// `SystemRunner`; tests plug in recorded outputs instead.
+pub trait CommandRunner: Send + Sync {
+    /// Runs `program` with `args` like `Command::output`. A command that exceeds
+    /// `timeout` must be reported as an `ErrorKind::TimedOut` error.
+    fn run(&self, program: &str, args: &[&str], timeout: Duration) -> io::Result<Output>;
+}
+
+/// Runs commands as child processes and kills them once the timeout expires
+pub struct SystemRunner;
+
+impl CommandRunner for SystemRunner {
+    fn run(&self, program: &str, args: &[&str], timeout: Duration) -> io::Result<Output> {
+        let mut child = Command::new(program)
+            .args(args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+
+        // Drain both pipes on their own threads so a chatty command can't
+        // block on a full pipe while we wait for it
+        let stdout = Self::drain(child.stdout.take());
+        let stderr = Self::drain(child.stderr.take());
+
+        let status = match Self::wait_with_timeout(&mut child, timeout)? {
+            Some(status) => status,
+            None => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(io::Error::new(
+                    io::ErrorKind::TimedOut,
+                    format!("'{}' timed out after {:?}", program, timeout),
+                ));
+            }
+        };
+
+        Ok(Output {
+            status,
+            stdout: stdout.join().unwrap_or_default(),
+            stderr: stderr.join().unwrap_or_default(),
+        })
+    }
+}
+
+impl SystemRunner {
+    fn wait_with_timeout(child: &mut Child, timeout: Duration) -> io::Result<Option<ExitStatus>> {
+        let deadline = Instant::now() + timeout;
+
+        loop {
+            if let Some(status) = child.try_wait()? {
+                return Ok(Some(status));
+            }
+            if Instant::now() >= deadline {
+                return Ok(None);
+            }
+            thread::sleep(POLL_INTERVAL);
+        }
+    }
+
+    fn drain<R: Read + Send + 'static>(pipe: Option<R>) -> thread::JoinHandle<Vec<u8>> {
+        thread::spawn(move || {
+            let mut buffer = Vec::new();
+            if let Some(mut pipe) = pipe {
+                let _ = pipe.read_to_end(&mut buffer);
+            }
+            buffer
+        })
+    }
+}
+
+/// Everything a probe needs to look at the machine: a command runner, the
+/// root directory that `/proc`, `/sys` and `/etc` paths are resolved against,
+/// and the timeout for external commands.
+pub struct ProbeContext {
+    runner: Arc<dyn CommandRunner>,
+    root: PathBuf,
+    timeout: Duration,
+    timed_out: Mutex<Vec<String>>,
+}
+
+impl ProbeContext {
+    pub fn new(
+        runner: Arc<dyn CommandRunner>,
+        root: impl Into<PathBuf>,
+        timeout: Duration,
+    ) -> Self {
+        Self {
+            runner,
+            root: root.into(),
+            timeout,
+            timed_out: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// A fresh context sharing the runner and root, used to give every module
+    /// its own timeout and timeout bookkeeping
+    pub fn fork(&self, timeout: Duration) -> Self {
+        Self::new(self.runner.clone(), self.root.clone(), timeout)
+    }
+
+    /// Runs an external command through the runner. Commands that time out
+    /// are recorded and reported as an `ErrorKind::TimedOut` error.
+    pub fn run(&self, program: &str, args: &[&str]) -> io::Result<Output> {
+        let result = self.runner.run(program, args, self.timeout);
+
+        if let Err(e) = &result {
+            if e.kind() == io::ErrorKind::TimedOut {
+                let command = std::iter::once(program)
+                    .chain(args.iter().copied())
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                if let Ok(mut timed_out) = self.timed_out.lock() {
+                    timed_out.push(command);
+                }
+            }
+        }
+
+        result
+    }
+
+    /// Resolves an absolute system path such as `/proc/version` against the root
+    pub fn path(&self, path: impl AsRef<Path>) -> PathBuf {
+        let path = path.as_ref();
+        self.root.join(path.strip_prefix("/").unwrap_or(path))
+    }
+
+    pub fn read_to_string(&self, path: impl AsRef<Path>) -> io::Result<String> {
+        fs::read_to_string(self.path(path))
+    }
+
+    /// Commands that were killed because they ran into the timeout
+    pub fn timed_out(&self) -> Vec<String> {
+        self.timed_out
+            .lock()
+            .map(|timed_out| timed_out.clone())
+            .unwrap_or_default()
+    }
+}
@@ -1,37 +1,41 @@
 use crate::config::Config;
-use crate::sysinfo::SystemInfo;
+use crate::modules::{field_owners, Module};
+use crate::sysinfo::{
+    CpuInfo, DiskInfo, InterfaceInfo, MemoryInfo, NetworkInfo, StorageInfo, SystemInfo, UsageBucket,
+};
 use regex::Regex;
 
+const GIB: f64 = 1024.0 * 1024.0 * 1024.0;
+
 pub struct DataRedactor;
 
 impl DataRedactor {
-    pub fn redact_system_info(info: &SystemInfo, config: &Config) -> SystemInfo {
-        SystemInfo {
-            os: info.os.clone(),         // OS info is generally safe
-            kernel: info.kernel.clone(), // Kernel version is safe
-            hostname: Self::redact_hostname(&info.hostname),
-            uptime: info.uptime.clone(), // Uptime is safe
-            cpu: Self::redact_cpu(&info.cpu, config.redact_cpu_model),
-            memory: Self::redact_memory(&info.memory),
-            storage: Self::redact_storage(&info.storage),
-            shell: info.shell.clone(),       // Shell is safe
-            desktop: info.desktop.clone(),   // DE is safe
-            terminal: info.terminal.clone(), // Terminal is safe
-            security: info.security.clone(), // Security info is generally safe for sharing
-            gpu: Self::redact_gpu(&info.gpu),
-            temperature: info.temperature.clone(), // Temperature is safe
-            network: Self::redact_network(&info.network),
-            packages: info.packages.clone(), // Package count is safe
+    /// Returns a copy of `info` with the user, hostname and the data of every
+    /// given module redacted. OS, kernel, uptime, shell and similar values are
+    /// generally safe to share and are kept as-is by their modules.
+    pub fn redact_system_info(
+        info: &SystemInfo,
+        config: &Config,
+        modules: &[&dyn Module],
+    ) -> SystemInfo {
+        let mut redacted = info.clone();
+        redacted.user = Self::redact_name(&info.user);
+        redacted.hostname = Self::redact_name(&info.hostname);
+
+        for module in field_owners(modules) {
+            module.redact(&mut redacted, config);
         }
+
+        redacted
     }
 
-    fn redact_hostname(hostname: &str) -> String {
-        if hostname.len() <= 2 {
-            return "*".repeat(hostname.len());
+    fn redact_name(name: &str) -> String {
+        let chars: Vec<char> = name.chars().collect();
+        if chars.len() <= 2 {
+            return "*".repeat(chars.len());
         }
 
         // Keep first and last character, redact middle
-        let chars: Vec<char> = hostname.chars().collect();
         let first = chars[0];
         let last = chars[chars.len() - 1];
         let middle_length = chars.len() - 2;
@@ -39,104 +43,136 @@ impl DataRedactor {
         format!("{}{}{}", first, "*".repeat(middle_length), last)
     }
 
-    fn redact_cpu(cpu: &str, redact_model: bool) -> String {
-        // Extract and preserve core count
-        let core_count = if let Ok(re) = Regex::new(r"\((\d+) cores?\)") {
-            if let Some(captures) = re.captures(cpu) {
-                format!("({} cores)", &captures[1])
-            } else {
-                String::new()
-            }
-        } else {
-            String::new()
-        };
-
-        let mut redacted = cpu.to_string();
+    pub fn redact_cpu(cpu: &Option<CpuInfo>, redact_model: bool) -> Option<CpuInfo> {
+        let mut cpu = cpu.clone()?;
 
-        // Only redact if the config option is enabled
+        // Only redact if the config option is enabled; core and thread counts are kept
         if redact_model {
             // Remove potentially identifying details but keep general info
             let cpu_patterns = [
                 (r"\bi[3579]-\d{4,}[A-Z]*\b", "iX-XXXX"), // Intel model numbers like i9-12900K, i7-3600X
                 (r"\bRyzen\s+[3579]\s+\d{4}[A-Z]*\b", "Ryzen X XXXX"), // AMD Ryzen like Ryzen 7 5800X
                 (r"@\s*\d+\.\d+\s*GHz", "@ X.X GHz"), // Redact specific frequencies
-                (r"\([^)]*cores?\)", ""), // Remove original core count (we'll add it back)
             ];
 
             for (pattern, replacement) in &cpu_patterns {
                 if let Ok(re) = Regex::new(pattern) {
-                    redacted = re.replace_all(&redacted, *replacement).to_string();
+                    cpu.brand = re.replace_all(&cpu.brand, *replacement).to_string();
                 }
             }
-        } else {
-            // Just remove the core count so we can add it back consistently
-            if let Ok(re) = Regex::new(r"\([^)]*cores?\)") {
-                redacted = re.replace_all(&redacted, "").to_string();
-            }
         }
 
-        // Clean up extra spaces and add back core count
-        redacted = redacted.split_whitespace().collect::<Vec<&str>>().join(" ");
+        // Clean up extra spaces
+        cpu.brand = cpu
+            .brand
+            .split_whitespace()
+            .collect::<Vec<&str>>()
+            .join(" ");
+        Some(cpu)
+    }
 
-        if !core_count.is_empty() {
-            format!("{} {}", redacted.trim(), core_count)
-        } else {
-            redacted
+    pub fn redact_memory(memory: &MemoryInfo) -> MemoryInfo {
+        // Keep the usage ratio and the size category, not the sizes
+        let category = |total| format!("{} GB", Self::memory_category(total));
+        let Some(bucket) = Self::bucket(memory.used, memory.total, category) else {
+            return memory.clone();
+        };
+        MemoryInfo {
+            used: None,
+            total: None,
+            bucket: Some(bucket),
         }
     }
 
-    fn redact_memory(memory: &str) -> String {
-        // Redact specific memory amounts but keep general size category
-        if let Ok(re) = Regex::new(r"(\d+\.\d+)\s*GB\s*/\s*(\d+\.\d+)\s*GB") {
-            if let Some(captures) = re.captures(memory) {
-                let used: f64 = captures[1].parse().unwrap_or(0.0);
-                let total: f64 = captures[2].parse().unwrap_or(0.0);
-
-                // Categorize memory size ranges
-                let total_category = match total {
-                    t if t < 4.0 => "< 4",
-                    t if t < 8.0 => "4-8",
-                    t if t < 16.0 => "8-16",
-                    t if t < 32.0 => "16-32",
-                    t if t < 64.0 => "32-64",
-                    t if t < 128.0 => "64-128",
-                    _ => "> 128",
-                };
-
-                let usage_percent = (used / total * 100.0).round() as u32;
-                return format!("{}% of {} GB", usage_percent, total_category);
-            }
+    pub fn redact_storage(storage: &Option<StorageInfo>) -> Option<StorageInfo> {
+        let storage = storage.as_ref()?;
+        let category = |total| Self::storage_category(total).to_string();
+
+        let disks = storage
+            .disks
+            .iter()
+            .map(|disk| {
+                let device = Self::redact_device(&disk.device);
+                match Self::bucket(disk.used, disk.total, category) {
+                    Some(bucket) => DiskInfo {
+                        device,
+                        used: None,
+                        total: None,
+                        bucket: Some(bucket),
+                        ..disk.clone()
+                    },
+                    None => DiskInfo {
+                        device,
+                        ..disk.clone()
+                    },
+                }
+            })
+            .collect();
+
+        Some(match Self::bucket(storage.used, storage.total, category) {
+            Some(bucket) => StorageInfo {
+                used: None,
+                total: None,
+                bucket: Some(bucket),
+                disks,
+            },
+            None => StorageInfo {
+                disks,
+                ..storage.clone()
+            },
+        })
+    }
+
+    /// Masks the name of a source device, which can carry a UUID or serial
+    /// (`/dev/mapper/luks-<UUID>`), keeping only the directory it is in
+    pub fn redact_device(device: &str) -> String {
+        match device.rsplit_once('/') {
+            Some((directory, _)) => format!("{}/XXXX", directory),
+            None => "XXXX".to_string(),
         }
+    }
 
-        memory.to_string()
+    /// The rounded usage percentage and the size category of `total`. Empty
+    /// or unknown amounts have nothing to hide and get none.
+    fn bucket(
+        used: Option<u64>,
+        total: Option<u64>,
+        category: impl Fn(u64) -> String,
+    ) -> Option<UsageBucket> {
+        let (used, total) = (used?, total.filter(|total| *total > 0)?);
+        Some(UsageBucket {
+            percent: (used as f64 / total as f64 * 100.0).round() as u32,
+            size: category(total),
+        })
     }
 
-    fn redact_storage(storage: &str) -> String {
-        // Similar to memory, categorize storage sizes
-        if let Ok(re) = Regex::new(r"(\d+\.\d+)\s*GB\s*/\s*(\d+\.\d+)\s*GB") {
-            if let Some(captures) = re.captures(storage) {
-                let used: f64 = captures[1].parse().unwrap_or(0.0);
-                let total: f64 = captures[2].parse().unwrap_or(0.0);
-
-                let total_category = match total {
-                    t if t < 128.0 => "< 128",
-                    t if t < 256.0 => "128-256",
-                    t if t < 512.0 => "256-512",
-                    t if t < 1024.0 => "512GB-1TB",
-                    t if t < 2048.0 => "1-2TB",
-                    t if t < 4096.0 => "2-4TB",
-                    _ => "> 4TB",
-                };
-
-                let usage_percent = (used / total * 100.0).round() as u32;
-                return format!("{}% of {}", usage_percent, total_category);
-            }
+    /// Size category label for an amount of memory
+    pub fn memory_category(total: u64) -> &'static str {
+        match total as f64 / GIB {
+            t if t < 4.0 => "< 4",
+            t if t < 8.0 => "4-8",
+            t if t < 16.0 => "8-16",
+            t if t < 32.0 => "16-32",
+            t if t < 64.0 => "32-64",
+            t if t < 128.0 => "64-128",
+            _ => "> 128",
         }
+    }
 
-        storage.to_string()
+    /// Size category label for a storage capacity
+    pub fn storage_category(total: u64) -> &'static str {
+        match total as f64 / GIB {
+            t if t < 128.0 => "< 128",
+            t if t < 256.0 => "128-256",
+            t if t < 512.0 => "256-512",
+            t if t < 1024.0 => "512GB-1TB",
+            t if t < 2048.0 => "1-2TB",
+            t if t < 4096.0 => "2-4TB",
+            _ => "> 4TB",
+        }
     }
 
-    fn redact_gpu(gpu: &str) -> String {
+    pub fn redact_gpu(gpu: &str) -> String {
         if gpu == "Unknown" {
             return gpu.to_string();
         }
@@ -161,30 +197,55 @@ impl DataRedactor {
         redacted
     }
 
-    fn redact_network(network: &str) -> String {
-        if network == "Unknown" {
-            return network.to_string();
-        }
+    pub fn redact_network(network: &Option<NetworkInfo>) -> Option<NetworkInfo> {
+        let network = network.as_ref()?;
 
-        // Redact IP addresses but keep interface names
-        if let Ok(re) = Regex::new(r"(\d+\.\d+\.\d+\.\d+)\s*\(([^)]+)\)") {
-            if let Some(captures) = re.captures(network) {
-                let interface = &captures[2];
-                return format!("XXX.XXX.XXX.XXX ({})", interface);
-            }
-        }
+        // Redact addresses but keep interface names and link details, except
+        // for names derived from the MAC address
+        let redact_all = |addresses: &[String]| -> Vec<String> {
+            addresses
+                .iter()
+                .map(|address| Self::redact_address(address))
+                .collect()
+        };
+        let interfaces = network
+            .interfaces
+            .iter()
+            .map(|interface| InterfaceInfo {
+                name: Self::redact_interface_name(&interface.name),
+                mac: interface
+                    .mac
+                    .as_ref()
+                    .map(|_| "XX:XX:XX:XX:XX:XX".to_string()),
+                ipv4: redact_all(&interface.ipv4),
+                ipv6: redact_all(&interface.ipv6),
+                ..interface.clone()
+            })
+            .collect();
+
+        Some(NetworkInfo {
+            interface: Self::redact_interface_name(&network.interface),
+            addresses: redact_all(&network.addresses),
+            interfaces,
+        })
+    }
 
-        // If it's just an interface name, keep it
-        if !network.contains('.') {
-            return network.to_string();
+    /// Masks the MAC address in predictable interface names such as
+    /// `enx001122334455`, other names are kept
+    pub fn redact_interface_name(name: &str) -> String {
+        match Regex::new(r"^(en|wl|ww)x[0-9a-f]{12}$") {
+            Ok(re) if re.is_match(name) => format!("{}xXXXXXXXXXXXX", &name[..2]),
+            _ => name.to_string(),
         }
+    }
 
-        // Fallback: redact any IP-like patterns
-        if let Ok(re) = Regex::new(r"\d+\.\d+\.\d+\.\d+") {
-            return re.replace_all(network, "XXX.XXX.XXX.XXX").to_string();
+    /// Masks an IPv4 or IPv6 address, keeping only its family recognizable
+    pub fn redact_address(address: &str) -> String {
+        if address.contains(':') {
+            "XXXX:XXXX::XXXX".to_string()
+        } else {
+            "XXX.XXX.XXX.XXX".to_string()
         }
-
-        network.to_string()
     }
 
     pub fn get_redaction_notice() -> String {
@@ -1,7 +1,9 @@
-use std::fs;
-use std::process::Command;
+use crate::probe::ProbeContext;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::Path;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SecurityInfo {
     pub firewall_status: String,
     pub selinux_status: String,
@@ -13,23 +15,48 @@ pub struct SecurityInfo {
     pub secure_boot: String,
 }
 
-impl SecurityInfo {
-    pub fn new() -> Self {
+impl Default for SecurityInfo {
+    fn default() -> Self {
         Self {
-            firewall_status: Self::get_firewall_status(),
-            selinux_status: Self::get_selinux_status(),
-            apparmor_status: Self::get_apparmor_status(),
-            package_updates: Self::get_package_updates(),
-            ssh_status: Self::get_ssh_status(),
-            fail2ban_status: Self::get_fail2ban_status(),
-            disk_encryption: Self::get_disk_encryption_status(),
-            secure_boot: Self::get_secure_boot_status(),
+            firewall_status: "Unknown".to_string(),
+            selinux_status: "Unknown".to_string(),
+            apparmor_status: "Unknown".to_string(),
+            package_updates: "Unknown".to_string(),
+            ssh_status: "Unknown".to_string(),
+            fail2ban_status: "Unknown".to_string(),
+            disk_encryption: "Unknown".to_string(),
+            secure_boot: "Unknown".to_string(),
         }
     }
+}
 
-    fn get_firewall_status() -> String {
+/// Counts the pending updates of one package manager
+type UpdateBackend = fn(&ProbeContext) -> Option<Updates>;
+
+/// Pending package updates, `security` is `None` when the package manager
+/// does not tell them apart
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Updates {
+    total: usize,
+    security: Option<usize>,
+}
+
+impl Updates {
+    fn add(self, other: Self) -> Self {
+        Self {
+            total: self.total + other.total,
+            security: match (self.security, other.security) {
+                (None, None) => None,
+                (a, b) => Some(a.unwrap_or(0) + b.unwrap_or(0)),
+            },
+        }
+    }
+}
+
+impl SecurityInfo {
+    pub fn get_firewall_status(ctx: &ProbeContext) -> String {
         // Check ufw first (Ubuntu/Debian default)
-        if let Ok(output) = Command::new("ufw").arg("status").output() {
+        if let Ok(output) = ctx.run("ufw", &["status"]) {
             if output.status.success() {
                 let status = String::from_utf8_lossy(&output.stdout);
                 if status.contains("Status: active") {
@@ -41,7 +68,7 @@ impl SecurityInfo {
         }
 
         // Check iptables
-        if let Ok(output) = Command::new("iptables").args(["-L", "-n"]).output() {
+        if let Ok(output) = ctx.run("iptables", &["-L", "-n"]) {
             if output.status.success() {
                 let rules = String::from_utf8_lossy(&output.stdout);
                 let rule_count = rules
@@ -60,7 +87,7 @@ impl SecurityInfo {
         }
 
         // Check firewalld
-        if let Ok(output) = Command::new("firewall-cmd").arg("--state").output() {
+        if let Ok(output) = ctx.run("firewall-cmd", &["--state"]) {
             if output.status.success() {
                 let status = String::from_utf8_lossy(&output.stdout);
                 if status.trim() == "running" {
@@ -72,8 +99,8 @@ impl SecurityInfo {
         "None detected".to_string()
     }
 
-    fn get_selinux_status() -> String {
-        if let Ok(contents) = fs::read_to_string("/sys/fs/selinux/enforce") {
+    pub fn get_selinux_status(ctx: &ProbeContext) -> String {
+        if let Ok(contents) = ctx.read_to_string("/sys/fs/selinux/enforce") {
             match contents.trim() {
                 "1" => return "Enforcing".to_string(),
                 "0" => return "Permissive".to_string(),
@@ -81,7 +108,7 @@ impl SecurityInfo {
             }
         }
 
-        if let Ok(output) = Command::new("getenforce").output() {
+        if let Ok(output) = ctx.run("getenforce", &[]) {
             if output.status.success() {
                 return String::from_utf8_lossy(&output.stdout).trim().to_string();
             }
@@ -90,11 +117,11 @@ impl SecurityInfo {
         "Not available".to_string()
     }
 
-    fn get_apparmor_status() -> String {
-        if let Ok(contents) = fs::read_to_string("/sys/module/apparmor/parameters/enabled") {
+    pub fn get_apparmor_status(ctx: &ProbeContext) -> String {
+        if let Ok(contents) = ctx.read_to_string("/sys/module/apparmor/parameters/enabled") {
             if contents.trim() == "Y" {
                 // Check how many profiles are loaded
-                if let Ok(profiles) = fs::read_to_string("/sys/kernel/security/apparmor/profiles") {
+                if let Ok(profiles) = ctx.read_to_string("/sys/kernel/security/apparmor/profiles") {
                     let profile_count = profiles.lines().count();
                     return format!("Active ({} profiles)", profile_count);
                 }
@@ -102,7 +129,7 @@ impl SecurityInfo {
             }
         }
 
-        if let Ok(output) = Command::new("aa-status").output() {
+        if let Ok(output) = ctx.run("aa-status", &[]) {
             if output.status.success() {
                 let status = String::from_utf8_lossy(&output.stdout);
                 if let Some(line) = status.lines().find(|l| l.contains("profiles are loaded")) {
@@ -114,55 +141,242 @@ impl SecurityInfo {
         "Not available".to_string()
     }
 
-    fn get_package_updates() -> String {
-        // Check for apt-based systems
-        if let Ok(output) = Command::new("apt").args(["list", "--upgradable"]).output() {
-            if output.status.success() {
-                let upgradeable = String::from_utf8_lossy(&output.stdout);
-                let count = upgradeable
-                    .lines()
-                    .filter(|line| line.contains("upgradable") && !line.starts_with("Listing"))
-                    .count();
+    /// Pending updates from the system package manager plus Flatpak, e.g.
+    /// "5 available (2 security)". Only local metadata is read, so the
+    /// counts are as fresh as the last refresh of the package lists.
+    pub fn get_package_updates(ctx: &ProbeContext) -> String {
+        Self::package_updates(ctx, dirs::home_dir().as_deref())
+    }
 
-                if count > 0 {
-                    return format!("{} available", count);
-                } else {
-                    return "Up to date".to_string();
-                }
-            }
+    /// Pending updates as in [`Self::get_package_updates`], looking for a
+    /// per-user Flatpak installation under `home`
+    pub fn package_updates(ctx: &ProbeContext, home: Option<&Path>) -> String {
+        // Each backend is tried only where its package database or
+        // configuration exists, so a stray `apt` or `pacman` binary on another
+        // distribution doesn't answer for it. rpm-ostree systems also carry
+        // dnf's configuration and come first.
+        let backends: [(&str, UpdateBackend); 7] = [
+            ("/var/lib/dpkg/status", Self::apt_updates),
+            ("/run/ostree-booted", Self::rpm_ostree_updates),
+            ("/etc/dnf", Self::dnf_updates),
+            ("/etc/zypp", Self::zypper_updates),
+            ("/var/lib/pacman/local", Self::pacman_updates),
+            ("/lib/apk/db/installed", Self::apk_updates),
+            ("/var/db/xbps", Self::xbps_updates),
+        ];
+        let system = backends
+            .iter()
+            .filter(|(database, _)| ctx.path(database).exists())
+            .find_map(|(_, backend)| backend(ctx));
+
+        // Flatpak is only asked when it has a system or user installation
+        let flatpak_installed = ctx.path("/var/lib/flatpak").exists()
+            || home.is_some_and(|home| ctx.path(home.join(".local/share/flatpak")).exists());
+        let flatpak = flatpak_installed
+            .then(|| Self::flatpak_updates(ctx))
+            .flatten();
+
+        let Some(updates) = [system, flatpak].into_iter().flatten().reduce(Updates::add) else {
+            return "Unknown".to_string();
+        };
+
+        match updates {
+            Updates { total: 0, .. } => "Up to date".to_string(),
+            Updates {
+                total,
+                security: Some(security),
+            } if security > 0 => format!("{} available ({} security)", total, security),
+            Updates { total, .. } => format!("{} available", total),
+        }
+    }
+
+    /// `apt list --upgradable` reads the downloaded package lists; updates
+    /// from a `-security` pocket are security updates
+    fn apt_updates(ctx: &ProbeContext) -> Option<Updates> {
+        let output = ctx.run("apt", &["list", "--upgradable"]).ok()?;
+        if !output.status.success() {
+            return None;
+        }
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let suites: Vec<&str> = stdout
+            .lines()
+            .filter(|line| line.contains("upgradable from"))
+            .filter_map(|line| line.split_whitespace().next()?.split_once('/'))
+            .map(|(_, suites)| suites)
+            .collect();
+        Some(Updates {
+            total: suites.len(),
+            security: Some(
+                suites
+                    .iter()
+                    .filter(|suites| suites.split(',').any(|suite| suite.ends_with("-security")))
+                    .count(),
+            ),
+        })
+    }
+
+    /// Updates `rpm-ostree` found in its last automatic check
+    fn rpm_ostree_updates(ctx: &ProbeContext) -> Option<Updates> {
+        let output = ctx.run("rpm-ostree", &["status", "--json"]).ok()?;
+        if !output.status.success() {
+            return None;
         }
 
-        // Check for yum/dnf-based systems
-        if let Ok(output) = Command::new("dnf")
-            .args(["check-update", "--quiet"])
-            .output()
-        {
+        let status: Value = serde_json::from_slice(&output.stdout).ok()?;
+        let total = match status.get("cached-update") {
+            Some(Value::Object(update)) => update
+                .get("rpm-diff")
+                .and_then(|diff| diff.get("upgraded"))
+                .and_then(Value::as_array)
+                .map_or(0, Vec::len),
+            _ => 0,
+        };
+        Some(Updates {
+            total,
+            security: None,
+        })
+    }
+
+    /// `dnf check-update` against the metadata cache; it exits with 100 when
+    /// there are updates
+    fn dnf_updates(ctx: &ProbeContext) -> Option<Updates> {
+        let count = |args: &[&str]| -> Option<usize> {
+            let output = ctx.run("dnf", args).ok()?;
             match output.status.code() {
-                Some(0) => return "Up to date".to_string(),
-                Some(100) => {
-                    let updates = String::from_utf8_lossy(&output.stdout);
-                    let count = updates
+                Some(0) => Some(0),
+                Some(100) => Some(
+                    String::from_utf8_lossy(&output.stdout)
                         .lines()
+                        .take_while(|line| !line.starts_with("Obsoleting"))
                         .filter(|line| !line.trim().is_empty())
-                        .count();
-                    return format!("{} available", count);
-                }
-                _ => {}
+                        .count(),
+                ),
+                _ => None,
             }
+        };
+
+        Some(Updates {
+            total: count(&["check-update", "--quiet", "--cacheonly"])?,
+            security: count(&["check-update", "--quiet", "--cacheonly", "--security"]),
+        })
+    }
+
+    /// `zypper list-updates` and the needed security patches, both without
+    /// refreshing the repositories
+    fn zypper_updates(ctx: &ProbeContext) -> Option<Updates> {
+        let table_rows = |args: &[&str]| -> Option<Vec<String>> {
+            let output = ctx.run("zypper", args).ok()?;
+            if !output.status.success() {
+                return None;
+            }
+            Some(
+                String::from_utf8_lossy(&output.stdout)
+                    .lines()
+                    // Rows come after the `---+---` separator below the header
+                    .skip_while(|line| !line.starts_with("--"))
+                    .skip(1)
+                    .filter(|line| line.contains('|'))
+                    .map(str::to_string)
+                    .collect(),
+            )
+        };
+
+        let updates = table_rows(&["--non-interactive", "--no-refresh", "list-updates"])?;
+        let patches = table_rows(&[
+            "--non-interactive",
+            "--no-refresh",
+            "list-patches",
+            "--category",
+            "security",
+        ]);
+        Some(Updates {
+            total: updates.len(),
+            security: patches.map(|patches| {
+                patches
+                    .iter()
+                    .filter(|row| row.split('|').any(|cell| cell.trim() == "needed"))
+                    .count()
+            }),
+        })
+    }
+
+    /// `pacman -Qu` compares the installed packages with the sync databases
+    /// from the last `pacman -Sy`; it exits with 1 when nothing is outdated
+    fn pacman_updates(ctx: &ProbeContext) -> Option<Updates> {
+        let output = ctx.run("pacman", &["-Qu"]).ok()?;
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let nothing_outdated = output.status.code() == Some(1) && stdout.is_empty();
+        if !output.status.success() && !nothing_outdated {
+            return None;
         }
+        Some(Updates {
+            total: Self::count_lines(&stdout),
+            security: None,
+        })
+    }
 
-        "Unknown".to_string()
+    /// `apk list --upgradable` uses the cached repository indexes
+    fn apk_updates(ctx: &ProbeContext) -> Option<Updates> {
+        let output = ctx.run("apk", &["list", "--upgradable"]).ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        Some(Updates {
+            total: String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .filter(|line| line.contains("upgradable from"))
+                .count(),
+            security: None,
+        })
+    }
+
+    /// A dry run of `xbps-install -u` against the local repository data
+    fn xbps_updates(ctx: &ProbeContext) -> Option<Updates> {
+        let output = ctx.run("xbps-install", &["-un"]).ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        Some(Updates {
+            total: String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .filter(|line| line.split_whitespace().nth(1) == Some("update"))
+                .count(),
+            security: None,
+        })
+    }
+
+    /// Installed refs whose latest known commit differs from the deployed one
+    fn flatpak_updates(ctx: &ProbeContext) -> Option<Updates> {
+        let output = ctx
+            .run("flatpak", &["list", "--columns=application,active,latest"])
+            .ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        Some(Updates {
+            total: String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .filter(|line| {
+                    let columns: Vec<&str> = line.split('\t').map(str::trim).collect();
+                    matches!(columns.as_slice(), [_, active, latest, ..]
+                        if !latest.is_empty() && *latest != "-" && active != latest)
+                })
+                .count(),
+            security: None,
+        })
+    }
+
+    fn count_lines(text: &str) -> usize {
+        text.lines().filter(|line| !line.trim().is_empty()).count()
     }
 
-    fn get_ssh_status() -> String {
+    pub fn get_ssh_status(ctx: &ProbeContext) -> String {
         // Check for SSH daemon on different systems
         let ssh_services = ["sshd", "ssh", "openssh"];
 
         for service in &ssh_services {
-            if let Ok(output) = Command::new("systemctl")
-                .args(["is-active", service])
-                .output()
-            {
+            if let Ok(output) = ctx.run("systemctl", &["is-active", service]) {
                 let status = String::from_utf8_lossy(&output.stdout);
                 match status.trim() {
                     "active" => return "Running".to_string(),
@@ -174,7 +388,7 @@ impl SecurityInfo {
         }
 
         // Check if SSH is listening on port 22
-        if let Ok(output) = Command::new("ss").args(["-tlnp"]).output() {
+        if let Ok(output) = ctx.run("ss", &["-tlnp"]) {
             let netstat_output = String::from_utf8_lossy(&output.stdout);
             if netstat_output.contains(":22 ") {
                 return "Running (port 22)".to_string();
@@ -182,7 +396,7 @@ impl SecurityInfo {
         }
 
         // Alternative check with netstat
-        if let Ok(output) = Command::new("netstat").args(["-tlnp"]).output() {
+        if let Ok(output) = ctx.run("netstat", &["-tlnp"]) {
             let netstat_output = String::from_utf8_lossy(&output.stdout);
             if netstat_output.contains(":22 ") {
                 return "Running (port 22)".to_string();
@@ -192,19 +406,14 @@ impl SecurityInfo {
         "Stopped".to_string()
     }
 
-    fn get_fail2ban_status() -> String {
-        if let Ok(output) = Command::new("systemctl")
-            .args(["is-active", "fail2ban"])
-            .output()
-        {
+    pub fn get_fail2ban_status(ctx: &ProbeContext) -> String {
+        if let Ok(output) = ctx.run("systemctl", &["is-active", "fail2ban"]) {
             if output.status.success() {
                 let status = String::from_utf8_lossy(&output.stdout);
                 match status.trim() {
                     "active" => {
                         // Get jail count if possible
-                        if let Ok(jail_output) =
-                            Command::new("fail2ban-client").arg("status").output()
-                        {
+                        if let Ok(jail_output) = ctx.run("fail2ban-client", &["status"]) {
                             if jail_output.status.success() {
                                 let jail_status = String::from_utf8_lossy(&jail_output.stdout);
                                 if let Some(line) =
@@ -228,9 +437,9 @@ impl SecurityInfo {
         "Not installed".to_string()
     }
 
-    fn get_disk_encryption_status() -> String {
+    pub fn get_disk_encryption_status(ctx: &ProbeContext) -> String {
         // Check for LUKS encryption
-        if let Ok(output) = Command::new("lsblk").args(["-f"]).output() {
+        if let Ok(output) = ctx.run("lsblk", &["-f"]) {
             if output.status.success() {
                 let lsblk_output = String::from_utf8_lossy(&output.stdout);
                 if lsblk_output.contains("crypto_LUKS") {
@@ -244,7 +453,7 @@ impl SecurityInfo {
         }
 
         // Check for encrypted filesystems in /proc/mounts
-        if let Ok(contents) = fs::read_to_string("/proc/mounts") {
+        if let Ok(contents) = ctx.read_to_string("/proc/mounts") {
             if contents.contains("dm-crypt") {
                 return "dm-crypt detected".to_string();
             }
@@ -253,8 +462,8 @@ impl SecurityInfo {
         "None detected".to_string()
     }
 
-    fn get_secure_boot_status() -> String {
-        if let Ok(contents) = fs::read_to_string(
+    pub fn get_secure_boot_status(ctx: &ProbeContext) -> String {
+        if let Ok(contents) = ctx.read_to_string(
             "/sys/firmware/efi/efivars/SecureBoot-8be4df61-93ca-11d2-aa0d-00e098032b8c",
         ) {
             if contents.len() >= 5 {
@@ -268,7 +477,7 @@ impl SecurityInfo {
         }
 
         // Alternative check using mokutil
-        if let Ok(output) = Command::new("mokutil").arg("--sb-state").output() {
+        if let Ok(output) = ctx.run("mokutil", &["--sb-state"]) {
             if output.status.success() {
                 let status = String::from_utf8_lossy(&output.stdout);
                 if status.contains("SecureBoot enabled") {
@@ -1,102 +1,517 @@
+use crate::cache::ProbeCache;
+use crate::config::Config;
+use crate::modules::{field_owners, field_pointer, glob_match, Module};
+use crate::pci::PciIds;
+use crate::probe::ProbeContext;
 use crate::security::SecurityInfo;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
 use std::fs;
-use sysinfo::System;
+use std::net::{Ipv4Addr, Ipv6Addr};
+use std::path::{Path, PathBuf};
+use std::thread;
+use sysinfo::{CpuRefreshKind, RefreshKind, System};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SystemInfo {
-    pub os: String,
+    pub os: OsInfo,
     pub kernel: String,
+    /// Login name shown in the `user@hostname` header
+    pub user: String,
     pub hostname: String,
-    pub uptime: String,
-    pub cpu: String,
-    pub memory: String,
-    pub storage: String,
+    /// Seconds since boot
+    pub uptime: u64,
+    pub cpu: Option<CpuInfo>,
+    pub memory: MemoryInfo,
+    pub swap: MemoryInfo,
+    pub storage: Option<StorageInfo>,
     pub shell: String,
     pub desktop: String,
     pub terminal: String,
     pub security: SecurityInfo,
-    pub gpu: String,
-    pub temperature: String,
-    pub network: String,
-    pub packages: String,
+    pub gpus: Vec<GpuInfo>,
+    pub sensors: SensorInfo,
+    pub network: Option<NetworkInfo>,
+    pub packages: Vec<PackageCount>,
+    /// Modules whose probes were killed for exceeding their timeout
+    pub timeouts: Vec<ProbeTimeout>,
 }
 
-impl SystemInfo {
-    pub fn new() -> Self {
-        let mut sys = System::new_all();
-        sys.refresh_all();
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OsInfo {
+    /// Distribution or product name without the version, e.g. "Ubuntu"
+    pub name: String,
+    /// Version number such as "22.04", or the build ID of rolling releases
+    pub version: String,
+    /// Full human-readable name, e.g. "Ubuntu 22.04.4 LTS"
+    pub pretty_name: String,
+}
 
+impl Default for OsInfo {
+    fn default() -> Self {
         Self {
-            os: Self::get_os_info(),
-            kernel: Self::get_kernel_version(),
-            hostname: Self::get_hostname(),
-            uptime: Self::get_uptime(&sys),
-            cpu: Self::get_cpu_info(&sys),
-            memory: Self::get_memory_info(&sys),
-            storage: Self::get_storage_info(&sys),
-            shell: Self::get_shell(),
-            desktop: Self::get_desktop_environment(),
-            terminal: Self::get_terminal(),
-            security: SecurityInfo::new(),
-            gpu: Self::get_gpu_info(),
-            temperature: Self::get_temperature(),
-            network: Self::get_network_info(),
-            packages: Self::get_package_count(),
-        }
-    }
-
-    fn get_os_info() -> String {
-        // Check for OS environment variable override first
-        if let Ok(os_override) = std::env::var("OS") {
-            return os_override;
+            name: "Unknown".to_string(),
+            version: "Unknown".to_string(),
+            pretty_name: "Unknown".to_string(),
         }
-        
-        match std::env::consts::OS {
-            "linux" => Self::get_linux_os_info(),
-            "macos" => Self::get_macos_os_info(),
-            _ => std::env::consts::OS.to_string(),
-        }
-    }
-
-    fn get_linux_os_info() -> String {
-        if let Ok(contents) = fs::read_to_string("/etc/os-release") {
-            for line in contents.lines() {
-                if line.starts_with("PRETTY_NAME=") {
-                    return line
-                        .split('=')
-                        .nth(1)
-                        .unwrap_or("Unknown")
-                        .trim_matches('"')
-                        .to_string();
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CpuInfo {
+    pub brand: String,
+    /// Physical cores, falls back to the thread count when unknown
+    pub cores: usize,
+    /// Logical processors
+    pub threads: usize,
+}
+
+/// What screenshot mode keeps of a usage in place of the byte counts
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UsageBucket {
+    /// Rounded usage percentage
+    pub percent: u32,
+    /// Size category, such as "16-32 GB"
+    pub size: String,
+}
+
+impl UsageBucket {
+    /// Used and total amounts for a usage bar: the bytes, or the percentage
+    /// out of 100 once redacted
+    pub fn ratio(used: Option<u64>, total: Option<u64>, bucket: Option<&Self>) -> (u64, u64) {
+        match bucket {
+            Some(bucket) => (u64::from(bucket.percent), 100),
+            None => (used.unwrap_or(0), total.unwrap_or(0)),
+        }
+    }
+}
+
+/// Memory usage in bytes. Redacted values only have a `bucket`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MemoryInfo {
+    pub used: Option<u64>,
+    pub total: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bucket: Option<UsageBucket>,
+}
+
+/// Storage usage in bytes, summed over all real filesystems. Redacted values
+/// only have a `bucket`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StorageInfo {
+    pub used: Option<u64>,
+    pub total: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bucket: Option<UsageBucket>,
+    pub disks: Vec<DiskInfo>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiskInfo {
+    pub mount_point: String,
+    pub file_system: String,
+    /// Source device, or the device number for mounts without one
+    #[serde(default)]
+    pub device: String,
+    #[serde(default)]
+    pub read_only: bool,
+    pub used: Option<u64>,
+    pub total: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bucket: Option<UsageBucket>,
+}
+
+/// One line of the mount table
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MountEntry {
+    pub device: String,
+    pub mount_point: String,
+    pub file_system: String,
+    pub read_only: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NetworkInfo {
+    /// Interface of the default route
+    pub interface: String,
+    /// Addresses of `interface`, IPv4 first
+    pub addresses: Vec<String>,
+    /// Every interface except loopback
+    #[serde(default)]
+    pub interfaces: Vec<InterfaceInfo>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InterfaceInfo {
+    pub name: String,
+    /// Link state from `operstate`: "up", "down", "unknown", ...
+    pub state: String,
+    pub speed_mbps: Option<u32>,
+    pub mac: Option<String>,
+    pub wireless: bool,
+    pub ipv4: Vec<String>,
+    pub ipv6: Vec<String>,
+}
+
+/// A display controller on the PCI bus
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GpuInfo {
+    /// Vendor and model, e.g. "NVIDIA GeForce RTX 3070"
+    pub name: String,
+    /// Short vendor name such as "AMD"
+    pub vendor: String,
+    /// Kernel driver bound to the device
+    pub driver: Option<String>,
+    /// Dedicated video memory in bytes, reported by amdgpu
+    pub vram_bytes: Option<u64>,
+    /// PCI address, e.g. "0000:01:00.0"
+    pub pci_address: String,
+}
+
+/// Hardware monitoring readings
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SensorInfo {
+    pub temperatures: Vec<TemperatureSensor>,
+    pub fans: Vec<FanSensor>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TemperatureSensor {
+    /// Driver name of the hwmon device, e.g. "coretemp" or "nvme"
+    pub chip: String,
+    /// Sensor label such as "Package id 0", or "temp1" when unlabeled
+    pub label: String,
+    pub celsius: f64,
+    /// Critical temperature reported by the hardware
+    pub critical: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FanSensor {
+    pub chip: String,
+    pub label: String,
+    pub rpm: u32,
+}
+
+/// Unit temperatures are shown in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TemperatureUnit {
+    #[default]
+    Celsius,
+    Fahrenheit,
+}
+
+impl TemperatureUnit {
+    pub fn format(self, celsius: f64) -> String {
+        match self {
+            Self::Celsius => format!("{:.0}°C", celsius),
+            Self::Fahrenheit => format!("{:.0}°F", celsius * 9.0 / 5.0 + 32.0),
+        }
+    }
+}
+
+impl TemperatureSensor {
+    /// Name matched by `temperature_sensors`, e.g. "coretemp/Package id 0"
+    pub fn name(&self) -> String {
+        format!("{}/{}", self.chip, self.label)
+    }
+}
+
+impl FanSensor {
+    /// Name matched by `fan_sensors`, e.g. "thinkpad/fan1"
+    pub fn name(&self) -> String {
+        format!("{}/{}", self.chip, self.label)
+    }
+}
+
+/// One entry of the IPv4 routing table
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Route {
+    pub interface: String,
+    pub destination: u32,
+    pub mask: u32,
+    pub metric: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PackageCount {
+    pub manager: String,
+    pub count: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProbeTimeout {
+    pub module: String,
+    pub commands: Vec<String>,
+}
+
+impl Default for SystemInfo {
+    fn default() -> Self {
+        Self {
+            os: OsInfo::default(),
+            kernel: "Unknown".to_string(),
+            user: "Unknown".to_string(),
+            hostname: "Unknown".to_string(),
+            uptime: 0,
+            cpu: None,
+            memory: MemoryInfo {
+                used: None,
+                total: None,
+                bucket: None,
+            },
+            swap: MemoryInfo {
+                used: None,
+                total: None,
+                bucket: None,
+            },
+            storage: None,
+            shell: "Unknown".to_string(),
+            desktop: "Unknown".to_string(),
+            terminal: "Unknown".to_string(),
+            security: SecurityInfo::default(),
+            gpus: Vec::new(),
+            sensors: SensorInfo::default(),
+            network: None,
+            packages: Vec::new(),
+            timeouts: Vec::new(),
+        }
+    }
+}
+
+impl SystemInfo {
+    /// Collects the header information plus whatever the given modules probe
+    /// through `ctx`.
+    /// Modules run concurrently; fields belonging to modules that are not
+    /// passed in keep their "Unknown" defaults, except the user, hostname and
+    /// OS, which the header and logo always need. Modules sharing a field are
+    /// probed once. A command that times out only
+    /// loses its own part of the module's value.
+    pub fn new(
+        ctx: &ProbeContext,
+        modules: &[&dyn Module],
+        config: &Config,
+        cache: &ProbeCache,
+    ) -> Self {
+        let modules = field_owners(modules);
+        let results = thread::scope(|scope| {
+            let handles: Vec<_> = modules
+                .iter()
+                .map(|module| {
+                    scope.spawn(move || Self::collect_module(ctx, *module, config, cache))
+                })
+                .collect();
+
+            handles
+                .into_iter()
+                .map(|handle| handle.join().unwrap_or((Value::Null, Vec::new())))
+                .collect::<Vec<_>>()
+        });
+
+        let mut hostname = Self::get_hostname(ctx);
+        if config.fqdn {
+            if let Some(fqdn) = Self::get_fqdn(ctx, &hostname) {
+                hostname = fqdn;
+            }
+        }
+
+        let base = Self {
+            os: Self::get_os_info(ctx),
+            user: Self::get_user(ctx),
+            hostname,
+            ..Self::default()
+        };
+        let mut document = serde_json::to_value(&base).unwrap_or(Value::Null);
+        let mut timeouts = Vec::new();
+
+        for (module, (value, timed_out)) in modules.iter().zip(results) {
+            // Only the commands that timed out fell back; whatever the rest
+            // of the probe found is kept
+            if !timed_out.is_empty() {
+                timeouts.push(ProbeTimeout {
+                    module: module.name().to_string(),
+                    commands: timed_out,
+                });
+            }
+
+            if let Some(field) = module.field() {
+                if let Some(slot) = document.pointer_mut(&field_pointer(field)) {
+                    // A value cached by an older release may have another
+                    // shape; keep the default rather than fail the document
+                    if slot.is_null() || Self::same_kind(slot, &value) {
+                        *slot = value;
+                    }
                 }
             }
         }
-        "Linux".to_string()
+
+        let mut info: Self = serde_json::from_value(document).unwrap_or(base);
+        info.timeouts = timeouts;
+        for module in &modules {
+            module.apply_config(&mut info, config);
+        }
+        info
     }
 
-    fn get_macos_os_info() -> String {
-        use std::process::Command;
+    fn same_kind(a: &Value, b: &Value) -> bool {
+        std::mem::discriminant(a) == std::mem::discriminant(b)
+    }
 
-        if let Ok(output) = Command::new("sw_vers").arg("-productName").output() {
-            if let Ok(product) = String::from_utf8(output.stdout) {
-                if let Ok(version_output) = Command::new("sw_vers").arg("-productVersion").output()
-                {
-                    if let Ok(version) = String::from_utf8(version_output.stdout) {
-                        return format!("{} {}", product.trim(), version.trim());
-                    }
+    /// Runs one module's probe, serving it from the cache when the module has
+    /// a TTL configured. Returns the value and the commands that timed out.
+    fn collect_module(
+        ctx: &ProbeContext,
+        module: &dyn Module,
+        config: &Config,
+        cache: &ProbeCache,
+    ) -> (Value, Vec<String>) {
+        let ttl = config.cache_ttl(module.name());
+        let inputs = Self::cache_input_paths(ctx, module.cache_inputs());
+
+        if let Some(ttl) = ttl {
+            if let Some(value) = cache.load(module.name(), ttl, &inputs) {
+                return (value, Vec::new());
+            }
+        }
+
+        let module_ctx = ctx.fork(config.probe_timeout(module.name()));
+        let value = module.collect(&module_ctx);
+        let timed_out = module_ctx.timed_out();
+
+        // Never cache a fallback caused by a timeout
+        if ttl.is_some() && timed_out.is_empty() {
+            cache.store(module.name(), &inputs, &value);
+        }
+
+        (value, timed_out)
+    }
+
+    /// Resolves cache inputs: `~/` is the user's home directory and a `*`
+    /// component matches every entry of its directory, such as each Python
+    /// version's `site-packages`
+    fn cache_input_paths(ctx: &ProbeContext, patterns: &[&str]) -> Vec<PathBuf> {
+        let home = dirs::home_dir();
+        patterns
+            .iter()
+            .filter_map(|pattern| match pattern.strip_prefix("~/") {
+                Some(rest) => home.as_ref().map(|home| home.join(rest)),
+                None => Some(PathBuf::from(pattern)),
+            })
+            .flat_map(|pattern| {
+                let mut paths = vec![ctx.path("/")];
+                for component in pattern.components().skip(1) {
+                    let component = component.as_os_str().to_string_lossy();
+                    paths = if component.contains('*') {
+                        paths
+                            .iter()
+                            .filter_map(|dir| fs::read_dir(dir).ok())
+                            .flatten()
+                            .filter_map(|entry| entry.ok())
+                            .filter(|entry| {
+                                glob_match(&component, &entry.file_name().to_string_lossy())
+                            })
+                            .map(|entry| entry.path())
+                            .collect()
+                    } else {
+                        paths.iter().map(|dir| dir.join(&*component)).collect()
+                    };
                 }
-                return product.trim().to_string();
+                paths
+            })
+            .collect()
+    }
+
+    pub fn get_os_info(ctx: &ProbeContext) -> OsInfo {
+        // Check for OS environment variable override first
+        if let Ok(os_override) = std::env::var("OS") {
+            return OsInfo {
+                name: os_override.clone(),
+                pretty_name: os_override,
+                ..OsInfo::default()
+            };
+        }
+
+        match std::env::consts::OS {
+            "linux" => Self::get_linux_os_info(ctx),
+            "macos" => Self::get_macos_os_info(ctx),
+            other => OsInfo {
+                name: other.to_string(),
+                pretty_name: other.to_string(),
+                ..OsInfo::default()
+            },
+        }
+    }
+
+    pub fn get_linux_os_info(ctx: &ProbeContext) -> OsInfo {
+        let mut os = OsInfo {
+            name: "Linux".to_string(),
+            pretty_name: "Linux".to_string(),
+            ..OsInfo::default()
+        };
+
+        let Ok(contents) = ctx.read_to_string("/etc/os-release") else {
+            return os;
+        };
+
+        let mut build_id = None;
+        let mut pretty_name = None;
+        for line in contents.lines() {
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            let value = value.trim().trim_matches('"').to_string();
+            if value.is_empty() {
+                continue;
+            }
+
+            match key.trim() {
+                "NAME" => os.name = value,
+                "VERSION_ID" => os.version = value,
+                "BUILD_ID" => build_id = Some(value),
+                "PRETTY_NAME" => pretty_name = Some(value),
+                _ => {}
+            }
+        }
+
+        // Rolling releases such as Arch only have a build ID
+        if os.version == "Unknown" {
+            if let Some(build_id) = build_id {
+                os.version = build_id;
             }
         }
-        "macOS".to_string()
+        os.pretty_name = pretty_name.unwrap_or_else(|| os.name.clone());
+        os
     }
 
-    fn get_kernel_version() -> String {
-        use std::process::Command;
+    fn get_macos_os_info(ctx: &ProbeContext) -> OsInfo {
+        let output = |arg: &str| {
+            ctx.run("sw_vers", &[arg])
+                .ok()
+                .and_then(|output| String::from_utf8(output.stdout).ok())
+                .map(|text| text.trim().to_string())
+                .filter(|text| !text.is_empty())
+        };
+
+        let name = output("-productName").unwrap_or_else(|| "macOS".to_string());
+        match output("-productVersion") {
+            Some(version) => OsInfo {
+                pretty_name: format!("{} {}", name, version),
+                name,
+                version,
+            },
+            None => OsInfo {
+                pretty_name: name.clone(),
+                name,
+                ..OsInfo::default()
+            },
+        }
+    }
 
+    pub fn get_kernel_version(ctx: &ProbeContext) -> String {
         match std::env::consts::OS {
             "linux" => {
-                if let Ok(contents) = fs::read_to_string("/proc/version") {
+                if let Ok(contents) = ctx.read_to_string("/proc/version") {
                     if let Some(version) = contents.split_whitespace().nth(2) {
                         return version.to_string();
                     }
@@ -104,7 +519,7 @@ impl SystemInfo {
                 "Unknown".to_string()
             }
             "macos" => {
-                if let Ok(output) = Command::new("uname").arg("-r").output() {
+                if let Ok(output) = ctx.run("uname", &["-r"]) {
                     if let Ok(version) = String::from_utf8(output.stdout) {
                         return format!("Darwin {}", version.trim());
                     }
@@ -115,62 +530,214 @@ impl SystemInfo {
         }
     }
 
-    fn get_hostname() -> String {
-        if let Ok(hostname) = fs::read_to_string("/etc/hostname") {
-            hostname.trim().to_string()
-        } else {
-            "Unknown".to_string()
-        }
+    /// The person running hxfetch: the invoking user under sudo, otherwise
+    /// `$USER` or `$LOGNAME`, falling back to the passwd entry of the
+    /// effective UID
+    pub fn get_user(ctx: &ProbeContext) -> String {
+        Self::user_from_env(ctx, |var| std::env::var(var).ok())
     }
 
-    fn get_uptime(_sys: &System) -> String {
-        let uptime_seconds = System::uptime();
-        let days = uptime_seconds / 86400;
-        let hours = (uptime_seconds % 86400) / 3600;
-        let minutes = (uptime_seconds % 3600) / 60;
+    /// [`Self::get_user`] with environment variables read through `env`
+    pub fn user_from_env(ctx: &ProbeContext, env: impl Fn(&str) -> Option<String>) -> String {
+        ["SUDO_USER", "USER", "LOGNAME"]
+            .iter()
+            .filter_map(|var| env(var))
+            .find(|user| !user.is_empty())
+            .or_else(|| Self::get_effective_user(ctx))
+            .unwrap_or_else(|| "Unknown".to_string())
+    }
 
-        if days > 0 {
-            format!("{} days, {} hours, {} minutes", days, hours, minutes)
-        } else if hours > 0 {
-            format!("{} hours, {} minutes", hours, minutes)
-        } else {
-            format!("{} minutes", minutes)
+    /// Name of the effective UID from `/proc/self/status` and `/etc/passwd`
+    pub fn get_effective_user(ctx: &ProbeContext) -> Option<String> {
+        let status = ctx.read_to_string("/proc/self/status").ok()?;
+        // Uid: real effective saved filesystem
+        let uid = status
+            .lines()
+            .find_map(|line| line.strip_prefix("Uid:"))?
+            .split_whitespace()
+            .nth(1)?;
+
+        let passwd = ctx.read_to_string("/etc/passwd").ok()?;
+        passwd.lines().find_map(|line| {
+            let fields: Vec<&str> = line.split(':').collect();
+            match fields.as_slice() {
+                [name, _, entry_uid, ..] if *entry_uid == uid => Some(name.to_string()),
+                _ => None,
+            }
+        })
+    }
+
+    /// Kernel hostname (what `uname -n` reports), falling back to
+    /// `/etc/hostname` and the `uname` command where `/proc` isn't available
+    pub fn get_hostname(ctx: &ProbeContext) -> String {
+        ["/proc/sys/kernel/hostname", "/etc/hostname"]
+            .iter()
+            .filter_map(|path| ctx.read_to_string(path).ok())
+            .chain(
+                ctx.run("uname", &["-n"])
+                    .ok()
+                    .filter(|output| output.status.success())
+                    .and_then(|output| String::from_utf8(output.stdout).ok()),
+            )
+            .map(|hostname| hostname.trim().to_string())
+            .find(|hostname| !hostname.is_empty())
+            .unwrap_or_else(|| "Unknown".to_string())
+    }
+
+    /// Fully qualified name for `hostname` from `/etc/hosts`: the canonical
+    /// name of the entry listing the hostname, if it extends it with a domain
+    pub fn get_fqdn(ctx: &ProbeContext, hostname: &str) -> Option<String> {
+        if hostname.contains('.') {
+            return Some(hostname.to_string());
         }
+
+        let hosts = ctx.read_to_string("/etc/hosts").ok()?;
+        let prefix = format!("{}.", hostname);
+
+        hosts.lines().find_map(|line| {
+            let line = line.split('#').next().unwrap_or_default();
+            let mut names = line.split_whitespace().skip(1);
+            let canonical = names.next()?;
+            let listed = canonical == hostname || names.any(|name| name == hostname);
+            (listed && canonical.starts_with(&prefix)).then(|| canonical.to_string())
+        })
     }
 
-    fn get_cpu_info(sys: &System) -> String {
-        if let Some(cpu) = sys.cpus().first() {
-            let cpu_name = cpu.brand().trim();
-            let cpu_count = sys.cpus().len();
-            format!("{} ({} cores)", cpu_name, cpu_count)
-        } else {
-            "Unknown".to_string()
+    pub fn get_uptime() -> u64 {
+        System::uptime()
+    }
+
+    pub fn get_cpu_info() -> Option<CpuInfo> {
+        let sys =
+            System::new_with_specifics(RefreshKind::new().with_cpu(CpuRefreshKind::everything()));
+        let cpu = sys.cpus().first()?;
+        let threads = sys.cpus().len();
+
+        Some(CpuInfo {
+            brand: cpu.brand().trim().to_string(),
+            cores: sys.physical_core_count().unwrap_or(threads),
+            threads,
+        })
+    }
+
+    pub fn get_memory_info() -> MemoryInfo {
+        let mut sys = System::new();
+        sys.refresh_memory();
+
+        MemoryInfo {
+            used: Some(sys.used_memory()),
+            total: Some(sys.total_memory()),
+            bucket: None,
         }
     }
 
-    fn get_memory_info(sys: &System) -> String {
-        let total_memory = sys.total_memory();
-        let used_memory = sys.used_memory();
-        let total_gb = total_memory as f64 / 1024.0 / 1024.0 / 1024.0;
-        let used_gb = used_memory as f64 / 1024.0 / 1024.0 / 1024.0;
+    pub fn get_swap_info() -> MemoryInfo {
+        let mut sys = System::new();
+        sys.refresh_memory();
 
-        format!("{:.1} GB / {:.1} GB", used_gb, total_gb)
+        MemoryInfo {
+            used: Some(sys.used_swap()),
+            total: Some(sys.total_swap()),
+            bucket: None,
+        }
     }
 
-    fn get_storage_info(_sys: &System) -> String {
+    pub fn get_storage_info(ctx: &ProbeContext) -> Option<StorageInfo> {
         use sysinfo::Disks;
         let disks = Disks::new_with_refreshed_list();
+        let sizes: HashMap<String, (u64, u64)> = disks
+            .iter()
+            .map(|disk| {
+                (
+                    disk.mount_point().to_string_lossy().into_owned(),
+                    (disk.total_space(), disk.available_space()),
+                )
+            })
+            .collect();
+
+        // Without a mount table (macOS), every disk counts as its own device
+        let mounts = match ctx.read_to_string("/proc/self/mountinfo") {
+            Ok(contents) => Self::parse_mountinfo(&contents),
+            Err(_) => disks
+                .iter()
+                .map(|disk| MountEntry {
+                    device: disk.name().to_string_lossy().into_owned(),
+                    mount_point: disk.mount_point().to_string_lossy().into_owned(),
+                    file_system: disk.file_system().to_string_lossy().into_owned(),
+                    read_only: false,
+                })
+                .collect(),
+        };
+
+        Self::storage_from_mounts(&mounts, |mount_point| sizes.get(mount_point).copied())
+    }
 
-        let mut total_space = 0u64;
-        let mut total_used = 0u64;
-        let mut disk_count = 0;
+    /// Mounts listed in `/proc/self/mountinfo`. Block devices are identified by
+    /// their source, so btrfs subvolumes of one filesystem share an ID; other
+    /// mounts by the `major:minor` device number, which bind mounts share.
+    pub fn parse_mountinfo(contents: &str) -> Vec<MountEntry> {
+        contents
+            .lines()
+            .filter_map(|line| {
+                // `ID PARENT MAJOR:MINOR ROOT MOUNTPOINT OPTIONS [OPTIONAL...] - FSTYPE SOURCE SUPER`
+                let (mount, filesystem) = line.split_once(" - ")?;
+                let fields: Vec<&str> = mount.split_whitespace().collect();
+                let mut filesystem = filesystem.split_whitespace();
+                let file_system = filesystem.next()?;
+                let source = filesystem.next().unwrap_or("");
+
+                let device = if source.starts_with("/dev/") {
+                    source.to_string()
+                } else {
+                    fields.get(2)?.to_string()
+                };
+
+                Some(MountEntry {
+                    device,
+                    mount_point: Self::unescape_mount_path(fields.get(4)?),
+                    file_system: file_system.to_string(),
+                    read_only: fields.get(5)?.split(',').any(|option| option == "ro"),
+                })
+            })
+            .collect()
+    }
 
-        for disk in disks.iter() {
-            // Skip pseudo filesystems and temporary mounts
-            let mount_point = disk.mount_point().to_str().unwrap_or("");
-            let fs_type = disk.file_system().to_str().unwrap_or("");
+    /// Decodes the octal escapes (`\040` for a space) used in mount paths
+    fn unescape_mount_path(path: &str) -> String {
+        let mut output = String::new();
+        let mut rest = path;
+        while let Some(start) = rest.find('\\') {
+            output.push_str(&rest[..start]);
+            let code = rest.get(start + 1..start + 4);
+            match code.and_then(|code| u8::from_str_radix(code, 8).ok()) {
+                Some(byte) => {
+                    output.push(byte as char);
+                    rest = &rest[start + 4..];
+                }
+                None => {
+                    output.push('\\');
+                    rest = &rest[start + 1..];
+                }
+            }
+        }
+        output.push_str(rest);
+        output
+    }
+
+    /// Sums the real filesystems among `mounts`, counting every device once.
+    /// `sizes` returns the total and available bytes of a mount point.
+    pub fn storage_from_mounts(
+        mounts: &[MountEntry],
+        sizes: impl Fn(&str) -> Option<(u64, u64)>,
+    ) -> Option<StorageInfo> {
+        let mut disks = Vec::new();
+        let mut seen = HashSet::new();
+
+        for mount in mounts {
+            let mount_point = mount.mount_point.as_str();
+            let fs_type = mount.file_system.as_str();
 
-            // Skip common pseudo/temporary filesystems
+            // Skip pseudo filesystems and temporary mounts
             if mount_point.starts_with("/proc")
                 || mount_point.starts_with("/sys")
                 || mount_point.starts_with("/dev")
@@ -180,34 +747,74 @@ impl SystemInfo {
                 || fs_type == "devtmpfs"
                 || fs_type == "sysfs"
                 || fs_type == "proc"
-                || disk.total_space() == 0
             {
                 continue;
             }
 
-            total_space += disk.total_space();
-            total_used += disk.total_space() - disk.available_space();
-            disk_count += 1;
+            let Some((total, available)) = sizes(mount_point) else {
+                continue;
+            };
+            // The first mount of a device wins, the mount table lists parents
+            // before the bind mounts and subvolumes below them
+            if total == 0 || !seen.insert(mount.device.as_str()) {
+                continue;
+            }
+
+            disks.push(DiskInfo {
+                mount_point: mount_point.to_string(),
+                file_system: fs_type.to_string(),
+                device: mount.device.clone(),
+                read_only: mount.read_only,
+                used: Some(total.saturating_sub(available)),
+                total: Some(total),
+                bucket: None,
+            });
+        }
+
+        if disks.is_empty() {
+            None
+        } else {
+            Some(Self::sum_storage(disks))
         }
+    }
 
-        if disk_count > 0 {
-            let total_gb = total_space as f64 / 1024.0 / 1024.0 / 1024.0;
-            let used_gb = total_used as f64 / 1024.0 / 1024.0 / 1024.0;
+    /// Drops the disks not selected by the `storage_*` globs and recomputes
+    /// the totals
+    pub fn filter_storage(storage: &mut Option<StorageInfo>, config: &Config) {
+        let Some(info) = storage else {
+            return;
+        };
 
-            if disk_count == 1 {
-                format!("{:.1} GB / {:.1} GB", used_gb, total_gb)
+        let selected = |patterns: &[String], text: &str, default: bool| {
+            if patterns.is_empty() {
+                default
             } else {
-                format!(
-                    "{:.1} GB / {:.1} GB ({} drives)",
-                    used_gb, total_gb, disk_count
-                )
+                patterns.iter().any(|pattern| glob_match(pattern, text))
             }
-        } else {
-            "Unknown".to_string()
+        };
+        info.disks.retain(|disk| {
+            selected(&config.storage_include, &disk.mount_point, true)
+                && !selected(&config.storage_exclude, &disk.mount_point, false)
+                && selected(&config.storage_fs_include, &disk.file_system, true)
+                && !selected(&config.storage_fs_exclude, &disk.file_system, false)
+        });
+
+        *storage =
+            (!info.disks.is_empty()).then(|| Self::sum_storage(std::mem::take(&mut info.disks)));
+    }
+
+    /// Storage totals over `disks`
+    fn sum_storage(disks: Vec<DiskInfo>) -> StorageInfo {
+        let sum = |bytes: fn(&DiskInfo) -> Option<u64>| disks.iter().filter_map(bytes).sum();
+        StorageInfo {
+            used: Some(sum(|disk| disk.used)),
+            total: Some(sum(|disk| disk.total)),
+            bucket: None,
+            disks,
         }
     }
 
-    fn get_shell() -> String {
+    pub fn get_shell() -> String {
         std::env::var("SHELL")
             .unwrap_or_else(|_| "Unknown".to_string())
             .split('/')
@@ -216,7 +823,7 @@ impl SystemInfo {
             .to_string()
     }
 
-    fn get_desktop_environment() -> String {
+    pub fn get_desktop_environment() -> String {
         let de_vars = [
             "XDG_CURRENT_DESKTOP",
             "DESKTOP_SESSION",
@@ -232,307 +839,667 @@ impl SystemInfo {
         "Unknown".to_string()
     }
 
-    fn get_terminal() -> String {
+    pub fn get_terminal() -> String {
         std::env::var("TERM").unwrap_or_else(|_| "Unknown".to_string())
     }
 
-    fn get_gpu_info() -> String {
-        use std::process::Command;
-
-        // Try NVIDIA first (most specific)
-        if let Ok(output) = Command::new("nvidia-smi")
-            .args(["--query-gpu=name", "--format=csv,noheader,nounits"])
-            .output()
-        {
-            if output.status.success() {
-                let nvidia_output = String::from_utf8_lossy(&output.stdout);
-                if let Some(gpu_name) = nvidia_output.lines().next() {
-                    if !gpu_name.trim().is_empty() {
-                        return format!("NVIDIA {}", gpu_name.trim());
-                    }
-                }
-            }
+    /// Every display controller (PCI class 0x03) found in `/sys/bus/pci`,
+    /// named from the PCI ID database
+    pub fn get_gpu_info(ctx: &ProbeContext) -> Vec<GpuInfo> {
+        let mut addresses: Vec<String> = fs::read_dir(ctx.path("/sys/bus/pci/devices"))
+            .map(|entries| {
+                entries
+                    .filter_map(|entry| Some(entry.ok()?.file_name().to_str()?.to_string()))
+                    .collect()
+            })
+            .unwrap_or_default();
+        addresses.sort();
+
+        let read_hex = |address: &str, file: &str| {
+            let value = ctx
+                .read_to_string(format!("/sys/bus/pci/devices/{}/{}", address, file))
+                .ok()?;
+            u32::from_str_radix(value.trim().trim_start_matches("0x"), 16).ok()
+        };
+        let displays: Vec<(String, u16, u16)> = addresses
+            .into_iter()
+            .filter(|address| read_hex(address, "class").is_some_and(|class| class >> 16 == 0x03))
+            .filter_map(|address| {
+                let vendor = read_hex(&address, "vendor")? as u16;
+                let device = read_hex(&address, "device")? as u16;
+                Some((address, vendor, device))
+            })
+            .collect();
+
+        if displays.is_empty() {
+            return Self::get_macos_gpu_info(ctx);
         }
 
-        // Try AMD GPU detection
-        if let Ok(output) = Command::new("rocm-smi")
-            .args(["--showproductname"])
-            .output()
-        {
-            if output.status.success() {
-                let amd_output = String::from_utf8_lossy(&output.stdout);
-                for line in amd_output.lines() {
-                    if line.contains("Card series:") || line.contains("Card model:") {
-                        if let Some(model) = line.split(":").nth(1) {
-                            return format!("AMD {}", model.trim());
-                        }
-                    }
+        let ids = PciIds::load(ctx);
+        let vram = Self::get_vram(ctx);
+        displays
+            .into_iter()
+            .map(|(address, vendor, device)| {
+                let (name, vendor) = ids.name(vendor, device);
+                GpuInfo {
+                    name,
+                    vendor,
+                    driver: Self::link_name(
+                        ctx,
+                        &format!("/sys/bus/pci/devices/{}/driver", address),
+                    ),
+                    vram_bytes: vram.get(&address).copied(),
+                    pci_address: address,
                 }
-            }
-        }
+            })
+            .collect()
+    }
 
-        // Try lspci for comprehensive GPU detection
-        if let Ok(output) = Command::new("lspci").output() {
-            let lspci_output = String::from_utf8_lossy(&output.stdout);
-            for line in lspci_output.lines() {
-                let lower_line = line.to_lowercase();
-                if lower_line.contains("vga")
-                    || lower_line.contains("3d")
-                    || lower_line.contains("display")
-                {
-                    if let Some(gpu_part) = line.split(": ").nth(1) {
-                        let gpu_info = gpu_part.to_string();
-
-                        // Enhanced parsing for better GPU identification
-                        if lower_line.contains("nvidia")
-                            || gpu_info.to_lowercase().contains("geforce")
-                            || gpu_info.to_lowercase().contains("quadro")
-                            || gpu_info.to_lowercase().contains("tesla")
-                        {
-                            return Self::parse_nvidia_gpu(&gpu_info);
-                        } else if lower_line.contains("amd")
-                            || lower_line.contains("radeon")
-                            || gpu_info.to_lowercase().contains("radeon")
-                        {
-                            return Self::parse_amd_gpu(&gpu_info);
-                        } else if lower_line.contains("intel") {
-                            return Self::parse_intel_gpu(&gpu_info);
-                        }
-
-                        return gpu_info;
-                    }
+    /// VRAM size by PCI address, from the `mem_info_vram_total` of each DRM card
+    fn get_vram(ctx: &ProbeContext) -> HashMap<String, u64> {
+        let Ok(entries) = fs::read_dir(ctx.path("/sys/class/drm")) else {
+            return HashMap::new();
+        };
+
+        entries
+            .filter_map(|entry| entry.ok()?.file_name().to_str().map(str::to_string))
+            // Connectors such as card0-DP-1 share the card's device
+            .filter(|card| card.starts_with("card") && !card.contains('-'))
+            .filter_map(|card| {
+                let device = format!("/sys/class/drm/{}/device", card);
+                let address = Self::link_name(ctx, &device)?;
+                let bytes = ctx
+                    .read_to_string(format!("{}/mem_info_vram_total", device))
+                    .ok()?
+                    .trim()
+                    .parse::<u64>()
+                    .ok()
+                    .filter(|bytes| *bytes > 0)?;
+                Some((address, bytes))
+            })
+            .collect()
+    }
+
+    /// Last component of the target of a sysfs symlink
+    fn link_name(ctx: &ProbeContext, path: &str) -> Option<String> {
+        let target = fs::read_link(ctx.path(path)).ok()?;
+        Some(target.file_name()?.to_str()?.to_string())
+    }
+
+    fn get_macos_gpu_info(ctx: &ProbeContext) -> Vec<GpuInfo> {
+        if std::env::consts::OS != "macos" {
+            return Vec::new();
+        }
+        let Ok(output) = ctx.run("system_profiler", &["SPDisplaysDataType"]) else {
+            return Vec::new();
+        };
+
+        String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|line| line.trim().strip_prefix("Chipset Model:"))
+            .map(|model| {
+                let name = model.trim().to_string();
+                GpuInfo {
+                    vendor: name
+                        .split_whitespace()
+                        .next()
+                        .unwrap_or_default()
+                        .to_string(),
+                    name,
+                    driver: None,
+                    vram_bytes: None,
+                    pci_address: String::new(),
                 }
+            })
+            .collect()
+    }
+
+    /// Temperatures and fan speeds from `/sys/class/hwmon`, falling back to
+    /// the thermal zones on machines without hwmon drivers
+    pub fn get_sensor_info(ctx: &ProbeContext) -> SensorInfo {
+        let mut sensors = SensorInfo::default();
+
+        for dir in Self::numbered_entries(ctx, "/sys/class/hwmon", "hwmon") {
+            let read = |file: &str| {
+                ctx.read_to_string(format!("{}/{}", dir, file))
+                    .ok()
+                    .map(|value| value.trim().to_string())
+            };
+            let Some(chip) = read("name") else {
+                continue;
+            };
+            let files: Vec<String> = fs::read_dir(ctx.path(&dir))
+                .map(|entries| {
+                    entries
+                        .filter_map(|entry| entry.ok()?.file_name().to_str().map(str::to_string))
+                        .collect()
+                })
+                .unwrap_or_default();
+
+            for channel in Self::channels(&files, "temp") {
+                let millidegrees = |file: &str| {
+                    read(&format!("{}_{}", channel, file))?
+                        .parse::<i64>()
+                        .ok()
+                        .map(|value| value as f64 / 1000.0)
+                };
+                let Some(celsius) = millidegrees("input") else {
+                    continue;
+                };
+                sensors.temperatures.push(TemperatureSensor {
+                    chip: chip.clone(),
+                    label: read(&format!("{}_label", channel)).unwrap_or_else(|| channel.clone()),
+                    celsius,
+                    critical: millidegrees("crit").filter(|critical| *critical > 0.0),
+                });
             }
-        }
 
-        // Try glxinfo for OpenGL renderer info
-        if let Ok(output) = Command::new("glxinfo").args(["-B"]).output() {
-            let glx_output = String::from_utf8_lossy(&output.stdout);
-            for line in glx_output.lines() {
-                if line.trim().starts_with("OpenGL renderer string:") {
-                    if let Some(renderer) = line.split(": ").nth(1) {
-                        let renderer_info = renderer.trim();
-
-                        // Parse renderer string for better identification
-                        if renderer_info.to_lowercase().contains("nvidia")
-                            || renderer_info.to_lowercase().contains("geforce")
-                        {
-                            return Self::parse_nvidia_gpu(renderer_info);
-                        } else if renderer_info.to_lowercase().contains("amd")
-                            || renderer_info.to_lowercase().contains("radeon")
-                        {
-                            return Self::parse_amd_gpu(renderer_info);
-                        }
-
-                        return renderer_info.to_string();
-                    }
-                }
+            for channel in Self::channels(&files, "fan") {
+                let Some(rpm) =
+                    read(&format!("{}_input", channel)).and_then(|rpm| rpm.parse().ok())
+                else {
+                    continue;
+                };
+                sensors.fans.push(FanSensor {
+                    chip: chip.clone(),
+                    label: read(&format!("{}_label", channel)).unwrap_or_else(|| channel.clone()),
+                    rpm,
+                });
             }
         }
 
-        // macOS GPU detection
-        if std::env::consts::OS == "macos" {
-            if let Ok(output) = Command::new("system_profiler")
-                .args(["SPDisplaysDataType"])
-                .output()
-            {
-                let gpu_output = String::from_utf8_lossy(&output.stdout);
-                for line in gpu_output.lines() {
-                    if line.trim().starts_with("Chipset Model:") {
-                        if let Some(model) = line.split(":").nth(1) {
-                            return model.trim().to_string();
-                        }
-                    }
+        if sensors.temperatures.is_empty() {
+            for dir in Self::numbered_entries(ctx, "/sys/class/thermal", "thermal_zone") {
+                let Some(millidegrees) = ctx
+                    .read_to_string(format!("{}/temp", dir))
+                    .ok()
+                    .and_then(|temp| temp.trim().parse::<i64>().ok())
+                else {
+                    continue;
+                };
+                // Zones without a sensor read 0
+                if millidegrees <= 0 {
+                    continue;
                 }
+                let zone = dir.rsplit('/').next().unwrap_or_default().to_string();
+                sensors.temperatures.push(TemperatureSensor {
+                    chip: ctx
+                        .read_to_string(format!("{}/type", dir))
+                        .map(|kind| kind.trim().to_string())
+                        .unwrap_or_else(|_| zone.clone()),
+                    label: zone,
+                    celsius: millidegrees as f64 / 1000.0,
+                    critical: None,
+                });
             }
         }
 
-        "Unknown".to_string()
+        sensors
     }
 
-    fn parse_nvidia_gpu(gpu_info: &str) -> String {
-        // Clean up NVIDIA GPU names
-        let mut name = gpu_info.to_string();
-
-        // Remove common prefixes/suffixes
-        name = name.replace("NVIDIA Corporation", "NVIDIA");
-        name = name.replace("[", "").replace("]", "");
+    /// Paths of the entries `<prefix><N>` in `dir`, ordered by N
+    fn numbered_entries(ctx: &ProbeContext, dir: &str, prefix: &str) -> Vec<String> {
+        let mut entries: Vec<(u32, String)> = fs::read_dir(ctx.path(dir))
+            .map(|entries| {
+                entries
+                    .filter_map(|entry| {
+                        let name = entry.ok()?.file_name().to_str()?.to_string();
+                        let number = name.strip_prefix(prefix)?.parse().ok()?;
+                        Some((number, format!("{}/{}", dir, name)))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        entries.sort();
+        entries.into_iter().map(|(_, path)| path).collect()
+    }
 
-        // Clean up extra whitespace
-        name = name.split_whitespace().collect::<Vec<&str>>().join(" ");
+    /// Channels such as "temp1" and "temp2" that have an `_input` file, in order
+    fn channels(files: &[String], kind: &str) -> Vec<String> {
+        let mut numbers: Vec<u32> = files
+            .iter()
+            .filter_map(|file| {
+                file.strip_prefix(kind)?
+                    .strip_suffix("_input")?
+                    .parse()
+                    .ok()
+            })
+            .collect();
+        numbers.sort();
+        numbers
+            .into_iter()
+            .map(|number| format!("{}{}", kind, number))
+            .collect()
+    }
 
-        if !name.starts_with("NVIDIA")
-            && (name.contains("GeForce")
-                || name.contains("GTX")
-                || name.contains("RTX")
-                || name.contains("Quadro")
-                || name.contains("Tesla"))
-        {
-            format!("NVIDIA {}", name)
+    /// Narrows the sensors down to the ones selected by `temperature_sensors`
+    /// and `fan_sensors`. Without a selection the first temperature of every
+    /// chip and all fans are kept.
+    pub fn filter_sensors(sensors: &mut SensorInfo, config: &Config) {
+        if config.temperature_sensors.is_empty() {
+            let mut seen = HashSet::new();
+            sensors
+                .temperatures
+                .retain(|sensor| seen.insert(sensor.chip.clone()));
         } else {
-            name
+            sensors.temperatures.retain(|sensor| {
+                config
+                    .temperature_sensors
+                    .iter()
+                    .any(|pattern| glob_match(pattern, &sensor.name()))
+            });
         }
-    }
 
-    fn parse_amd_gpu(gpu_info: &str) -> String {
-        // Clean up AMD GPU names
-        let mut name = gpu_info.to_string();
-
-        // Remove common prefixes
-        name = name.replace("Advanced Micro Devices", "AMD");
-        name = name.replace("ATI Technologies Inc", "AMD");
-        name = name.replace("[AMD/ATI]", "AMD");
-        name = name.replace("[", "").replace("]", "");
-
-        // Clean up extra whitespace
-        name = name.split_whitespace().collect::<Vec<&str>>().join(" ");
-
-        if !name.starts_with("AMD") && name.contains("Radeon") {
-            format!("AMD {}", name)
-        } else {
-            name
+        if !config.fan_sensors.is_empty() {
+            sensors.fans.retain(|fan| {
+                config
+                    .fan_sensors
+                    .iter()
+                    .any(|pattern| glob_match(pattern, &fan.name()))
+            });
         }
     }
 
-    fn parse_intel_gpu(gpu_info: &str) -> String {
-        // Clean up Intel GPU names
-        let mut name = gpu_info.to_string();
-
-        // Remove common prefixes
-        name = name.replace("Intel Corporation", "Intel");
-        name = name.replace("[", "").replace("]", "");
-
-        // Clean up extra whitespace
-        name = name.split_whitespace().collect::<Vec<&str>>().join(" ");
-
-        if !name.starts_with("Intel") {
-            format!("Intel {}", name)
-        } else {
-            name
-        }
+    pub fn get_network_info(ctx: &ProbeContext) -> Option<NetworkInfo> {
+        let routes = ctx
+            .read_to_string("/proc/net/route")
+            .map(|contents| Self::parse_routes(&contents))
+            .unwrap_or_default();
+        let ipv4 = ctx
+            .read_to_string("/proc/net/fib_trie")
+            .map(|contents| Self::parse_local_addresses(&contents))
+            .unwrap_or_default();
+        let ipv6 = ctx
+            .read_to_string("/proc/net/if_inet6")
+            .map(|contents| Self::parse_if_inet6(&contents))
+            .unwrap_or_default();
+
+        let mut names: Vec<String> = fs::read_dir(ctx.path("/sys/class/net"))
+            .ok()?
+            .filter_map(|entry| Some(entry.ok()?.file_name().to_str()?.to_string()))
+            .filter(|name| name != "lo")
+            .collect();
+        names.sort();
+
+        let interfaces: Vec<InterfaceInfo> = names
+            .into_iter()
+            .map(|name| {
+                let dir = format!("/sys/class/net/{}", name);
+                let read = |file: &str| {
+                    ctx.read_to_string(format!("{}/{}", dir, file))
+                        .ok()
+                        .map(|value| value.trim().to_string())
+                };
+
+                InterfaceInfo {
+                    state: read("operstate").unwrap_or_else(|| "unknown".to_string()),
+                    // Negative or unreadable for links that are down or wireless
+                    speed_mbps: read("speed").and_then(|speed| speed.parse().ok()),
+                    mac: read("address").filter(|mac| mac != "00:00:00:00:00:00"),
+                    wireless: ctx.path(format!("{}/wireless", dir)).is_dir(),
+                    ipv4: ipv4
+                        .iter()
+                        .filter(|address| Self::route_interface(&routes, **address) == Some(&name))
+                        .map(|address| address.to_string())
+                        .collect(),
+                    ipv6: ipv6
+                        .iter()
+                        .filter(|(interface, _)| *interface == name)
+                        .map(|(_, address)| address.to_string())
+                        .collect(),
+                    name,
+                }
+            })
+            .collect();
+
+        let default_interface = routes
+            .iter()
+            .filter(|route| route.mask == 0)
+            .min_by_key(|route| route.metric)
+            .map(|route| route.interface.clone());
+        let primary = match default_interface {
+            Some(name) => interfaces.iter().find(|interface| interface.name == name),
+            None => interfaces.iter().find(|interface| interface.state == "up"),
+        }?;
+
+        Some(NetworkInfo {
+            interface: primary.name.clone(),
+            addresses: primary.ipv4.iter().chain(&primary.ipv6).cloned().collect(),
+            interfaces,
+        })
     }
 
-    fn get_temperature() -> String {
-        use std::process::Command;
+    /// Entries of `/proc/net/route`, whose addresses are the network-order
+    /// bytes printed as a host-order hex number
+    pub fn parse_routes(contents: &str) -> Vec<Route> {
+        let hex = |value: &str| u32::from_str_radix(value, 16).ok();
+        contents
+            .lines()
+            .skip(1)
+            .filter_map(|line| {
+                let fields: Vec<&str> = line.split_whitespace().collect();
+                Some(Route {
+                    interface: fields.first()?.to_string(),
+                    destination: u32::from_be(hex(fields.get(1)?)?),
+                    metric: fields.get(6)?.parse().ok()?,
+                    mask: u32::from_be(hex(fields.get(7)?)?),
+                })
+            })
+            .collect()
+    }
 
-        // Try different temperature sources
-        let temp_paths = [
-            "/sys/class/thermal/thermal_zone0/temp",
-            "/sys/class/thermal/thermal_zone1/temp",
-        ];
+    /// Interface of the most specific non-default route that covers `address`
+    fn route_interface(routes: &[Route], address: Ipv4Addr) -> Option<&String> {
+        let address = u32::from(address);
+        routes
+            .iter()
+            .filter(|route| route.mask != 0 && address & route.mask == route.destination)
+            .max_by_key(|route| route.mask.count_ones())
+            .map(|route| &route.interface)
+    }
 
-        for path in &temp_paths {
-            if let Ok(contents) = fs::read_to_string(path) {
-                if let Ok(temp_millicelsius) = contents.trim().parse::<i32>() {
-                    let temp_celsius = temp_millicelsius / 1000;
-                    if temp_celsius > 0 && temp_celsius < 150 {
-                        // Reasonable range
-                        return format!("{}°C", temp_celsius);
+    /// The machine's own IPv4 addresses, the `/32 host LOCAL` entries of
+    /// `/proc/net/fib_trie`, without loopback
+    pub fn parse_local_addresses(contents: &str) -> Vec<Ipv4Addr> {
+        let mut addresses = Vec::new();
+        let mut last = None;
+        for line in contents.lines() {
+            let line = line.trim_start();
+            if let Some(address) = line.strip_prefix("|-- ") {
+                last = address.trim().parse::<Ipv4Addr>().ok();
+            } else if line.starts_with("/32 host LOCAL") {
+                if let Some(address) = last.filter(|address| !address.is_loopback()) {
+                    if !addresses.contains(&address) {
+                        addresses.push(address);
                     }
                 }
             }
         }
+        addresses
+    }
 
-        // Try sensors command
-        if let Ok(output) = Command::new("sensors").output() {
-            let sensors_output = String::from_utf8_lossy(&output.stdout);
-            for line in sensors_output.lines() {
-                if line.contains("Core 0") || line.contains("temp1") {
-                    if let Some(temp_part) = line.split("+").nth(1) {
-                        if let Some(temp) = temp_part.split("°C").next() {
-                            return format!("{}°C", temp.trim());
-                        }
-                    }
+    /// `(interface, address)` pairs from `/proc/net/if_inet6`, without
+    /// loopback and link-local addresses
+    pub fn parse_if_inet6(contents: &str) -> Vec<(String, Ipv6Addr)> {
+        contents
+            .lines()
+            .filter_map(|line| {
+                let fields: Vec<&str> = line.split_whitespace().collect();
+                let address = Ipv6Addr::from(u128::from_str_radix(fields.first()?, 16).ok()?);
+                // Scope 0x10 is host (loopback), 0x20 link-local
+                let scope = u8::from_str_radix(fields.get(3)?, 16).ok()?;
+                if scope == 0x10 || scope == 0x20 {
+                    return None;
                 }
-            }
-        }
-
-        "Unknown".to_string()
+                Some((fields.get(5)?.to_string(), address))
+            })
+            .collect()
     }
 
-    fn get_network_info() -> String {
-        use std::process::Command;
-
-        // Get network interface info
-        if let Ok(output) = Command::new("ip")
-            .args(["route", "show", "default"])
-            .output()
+    /// Drops the interfaces not selected by the `network_*` settings
+    pub fn filter_network(network: &mut Option<NetworkInfo>, config: &Config) {
+        let Some(info) = network else {
+            return;
+        };
+        info.interfaces.retain(|interface| {
+            (config.network_include.is_empty()
+                || config
+                    .network_include
+                    .iter()
+                    .any(|pattern| glob_match(pattern, &interface.name)))
+                && !config
+                    .network_exclude
+                    .iter()
+                    .any(|pattern| glob_match(pattern, &interface.name))
+                && (config.network_show_down || interface.state != "down")
+        });
+
+        // The default route's interface may be filtered out, the first
+        // remaining interface that is up takes its place
+        if info
+            .interfaces
+            .iter()
+            .all(|interface| interface.name != info.interface)
         {
-            let route_output = String::from_utf8_lossy(&output.stdout);
-            if let Some(line) = route_output.lines().next() {
-                if let Some(dev_part) = line.split("dev ").nth(1) {
-                    if let Some(interface) = dev_part.split_whitespace().next() {
-                        // Get IP address for this interface
-                        if let Ok(ip_output) = Command::new("ip")
-                            .args(["addr", "show", interface])
-                            .output()
-                        {
-                            let ip_info = String::from_utf8_lossy(&ip_output.stdout);
-                            for ip_line in ip_info.lines() {
-                                if ip_line.trim().starts_with("inet ")
-                                    && !ip_line.contains("127.0.0.1")
-                                {
-                                    if let Some(ip_part) = ip_line.trim().split("inet ").nth(1) {
-                                        if let Some(ip) = ip_part.split("/").next() {
-                                            return format!("{} ({})", ip.trim(), interface);
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                        return interface.to_string();
-                    }
+            let primary = info
+                .interfaces
+                .iter()
+                .find(|interface| interface.state == "up")
+                .or(info.interfaces.first())
+                .map(|primary| {
+                    (
+                        primary.name.clone(),
+                        primary.ipv4.iter().chain(&primary.ipv6).cloned().collect(),
+                    )
+                });
+            match primary {
+                Some((name, addresses)) => {
+                    info.interface = name;
+                    info.addresses = addresses;
                 }
+                None => *network = None,
             }
         }
-
-        "Unknown".to_string()
     }
 
-    fn get_package_count() -> String {
-        use std::process::Command;
+    pub fn get_package_count(ctx: &ProbeContext) -> Vec<PackageCount> {
+        Self::count_packages(ctx, dirs::home_dir().as_deref())
+    }
 
-        // Try different package managers
-        let package_managers = [
-            ("dpkg", vec!["-l"]),
-            ("rpm", vec!["-qa"]),
-            ("pacman", vec!["-Q"]),
-            ("brew", vec!["list"]),
-            ("pkg", vec!["info"]),
-            ("xbps-query", vec!["-l"]),
+    /// Installed packages of every package manager that has any, including
+    /// the per-user ones under `home`. Databases that can be read directly are
+    /// parsed, the others are asked through their command.
+    pub fn count_packages(ctx: &ProbeContext, home: Option<&Path>) -> Vec<PackageCount> {
+        let in_home = |path: &str| home.map(|home| home.join(path));
+        // Commands are only run where the manager's database exists, so
+        // missing ones don't cost a spawn or run into the probe timeout
+        let installed = |databases: &[&str]| databases.iter().any(|path| ctx.path(path).exists());
+        let count_if = |installed: bool, program: &str, args: &[&str], header: usize| {
+            installed
+                .then(|| Self::count_lines(ctx, program, args, header))
+                .flatten()
+        };
+        let user_flatpaks =
+            in_home(".local/share/flatpak/app").is_some_and(|path| ctx.path(path).exists());
+
+        let counts = [
+            ("dpkg", Self::count_dpkg(ctx)),
+            (
+                "rpm",
+                count_if(
+                    installed(&["/var/lib/rpm", "/usr/lib/sysimage/rpm"]),
+                    "rpm",
+                    &["-qa"],
+                    0,
+                ),
+            ),
+            ("pacman", Self::count_dirs(ctx, "/var/lib/pacman/local")),
+            ("apk", Self::count_apk(ctx)),
+            ("emerge", Self::count_emerge(ctx)),
+            (
+                "xbps",
+                count_if(installed(&["/var/db/xbps"]), "xbps-query", &["-l"], 0),
+            ),
+            ("nix-system", Self::count_nix_system(ctx)),
+            (
+                "nix-default",
+                Self::count_nix_profile(ctx, Path::new("/nix/var/nix/profiles/default")),
+            ),
+            (
+                "nix-user",
+                in_home(".nix-profile").and_then(|profile| Self::count_nix_profile(ctx, &profile)),
+            ),
+            (
+                "flatpak",
+                count_if(
+                    installed(&["/var/lib/flatpak/app"]) || user_flatpaks,
+                    "flatpak",
+                    &["list"],
+                    0,
+                ),
+            ),
+            // `snap list` always prints a header
+            (
+                "snap",
+                count_if(installed(&["/var/lib/snapd/snaps"]), "snap", &["list"], 1),
+            ),
+            (
+                "brew",
+                count_if(
+                    installed(&[
+                        "/home/linuxbrew/.linuxbrew/Cellar",
+                        "/opt/homebrew/Cellar",
+                        "/usr/local/Cellar",
+                    ]),
+                    "brew",
+                    &["list"],
+                    0,
+                ),
+            ),
+            (
+                "pkg",
+                count_if(
+                    installed(&["/var/db/pkg/local.sqlite"]),
+                    "pkg",
+                    &["info"],
+                    0,
+                ),
+            ),
+            (
+                "cargo",
+                in_home(".cargo/.crates2.json").and_then(|path| Self::count_cargo(ctx, &path)),
+            ),
+            (
+                "pip",
+                in_home(".local/lib").and_then(|path| Self::count_pip(ctx, &path)),
+            ),
         ];
 
-        for (cmd, args) in &package_managers {
-            if let Ok(output) = Command::new(cmd).args(args).output() {
-                if output.status.success() {
-                    let package_output = String::from_utf8_lossy(&output.stdout);
-                    let count = package_output
-                        .lines()
-                        .filter(|line| !line.trim().is_empty() && !line.starts_with("Listing"))
-                        .count();
-
-                    if count > 0 {
-                        return format!("{} ({})", count, cmd);
-                    }
-                }
-            }
+        counts
+            .into_iter()
+            .filter_map(|(manager, count)| {
+                Some(PackageCount {
+                    manager: manager.to_string(),
+                    count: count.filter(|count| *count > 0)?,
+                })
+            })
+            .collect()
+    }
+
+    /// Non-empty output lines of a command, less `header` lines
+    fn count_lines(
+        ctx: &ProbeContext,
+        program: &str,
+        args: &[&str],
+        header: usize,
+    ) -> Option<usize> {
+        let output = ctx.run(program, args).ok()?;
+        if !output.status.success() {
+            return None;
         }
+        let count = String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .count();
+        Some(count.saturating_sub(header))
+    }
 
-        // Try flatpak
-        if let Ok(output) = Command::new("flatpak").args(["list"]).output() {
-            if output.status.success() {
-                let flatpak_output = String::from_utf8_lossy(&output.stdout);
-                let flatpak_count = flatpak_output
-                    .lines()
-                    .filter(|line| !line.trim().is_empty())
-                    .count();
+    /// Subdirectories of `dir`, each one an installed package
+    fn count_dirs(ctx: &ProbeContext, dir: impl AsRef<Path>) -> Option<usize> {
+        let entries = fs::read_dir(ctx.path(dir)).ok()?;
+        Some(
+            entries
+                .filter_map(|entry| entry.ok())
+                .filter(|entry| entry.path().is_dir())
+                .count(),
+        )
+    }
 
-                if flatpak_count > 0 {
-                    return format!("{} (flatpak)", flatpak_count);
-                }
-            }
+    /// Packages in the `install ok installed` state; removed packages that
+    /// left config files behind are listed too but don't count
+    fn count_dpkg(ctx: &ProbeContext) -> Option<usize> {
+        let status = ctx.read_to_string("/var/lib/dpkg/status").ok()?;
+        Some(
+            status
+                .lines()
+                .filter_map(|line| line.strip_prefix("Status:"))
+                .filter(|state| state.split_whitespace().last() == Some("installed"))
+                .count(),
+        )
+    }
+
+    /// One `P:` (package name) line per package
+    fn count_apk(ctx: &ProbeContext) -> Option<usize> {
+        let installed = ctx.read_to_string("/lib/apk/db/installed").ok()?;
+        Some(
+            installed
+                .lines()
+                .filter(|line| line.starts_with("P:"))
+                .count(),
+        )
+    }
+
+    /// Portage keeps one directory per package in `/var/db/pkg/<category>`
+    fn count_emerge(ctx: &ProbeContext) -> Option<usize> {
+        let categories = fs::read_dir(ctx.path("/var/db/pkg")).ok()?;
+        Some(
+            categories
+                .filter_map(|entry| entry.ok())
+                .filter_map(|entry| {
+                    Self::count_dirs(ctx, Path::new("/var/db/pkg").join(entry.file_name()))
+                })
+                .sum(),
+        )
+    }
+
+    /// Store paths the NixOS system profile depends on
+    fn count_nix_system(ctx: &ProbeContext) -> Option<usize> {
+        if !ctx.path("/run/current-system").exists() {
+            return None;
         }
+        Self::count_lines(ctx, "nix-store", &["-qR", "/run/current-system/sw"], 0)
+    }
 
-        "Unknown".to_string()
+    /// Packages in a profile's `manifest.json` (`nix profile`), or in the
+    /// `manifest.nix` of profiles managed by `nix-env`
+    fn count_nix_profile(ctx: &ProbeContext, profile: &Path) -> Option<usize> {
+        if let Ok(manifest) = ctx.read_to_string(profile.join("manifest.json")) {
+            let manifest: Value = serde_json::from_str(&manifest).ok()?;
+            // A list up to version 2, keyed by name since version 3
+            return match manifest.get("elements")? {
+                Value::Array(elements) => Some(elements.len()),
+                Value::Object(elements) => Some(elements.len()),
+                _ => None,
+            };
+        }
+
+        let manifest = ctx.read_to_string(profile.join("manifest.nix")).ok()?;
+        Some(manifest.matches("type = \"derivation\";").count())
+    }
+
+    /// Crates installed with `cargo install`
+    fn count_cargo(ctx: &ProbeContext, path: &Path) -> Option<usize> {
+        let crates: Value = serde_json::from_str(&ctx.read_to_string(path).ok()?).ok()?;
+        Some(crates.get("installs")?.as_object()?.len())
+    }
+
+    /// Packages installed with `pip install --user`. System-wide Python
+    /// packages mostly come from the distribution and are counted there.
+    fn count_pip(ctx: &ProbeContext, lib: &Path) -> Option<usize> {
+        let pythons = fs::read_dir(ctx.path(lib)).ok()?;
+        Some(
+            pythons
+                .filter_map(|entry| entry.ok())
+                .filter(|entry| entry.file_name().to_string_lossy().starts_with("python"))
+                .filter_map(|entry| fs::read_dir(entry.path().join("site-packages")).ok())
+                .flatten()
+                .filter_map(|entry| entry.ok())
+                .filter(|entry| {
+                    let name = entry.file_name();
+                    let name = name.to_string_lossy();
+                    name.ends_with(".dist-info") || name.ends_with(".egg-info")
+                })
+                .count(),
+        )
     }
 }
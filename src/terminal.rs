@@ -0,0 +1,254 @@
+use serde::{Deserialize, Serialize};
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+/// Marks the end of a truncated line
+const ELLIPSIS: char = '…';
+
+/// What to do with info lines wider than the terminal
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Overflow {
+    /// Cut the line and end it with an ellipsis
+    #[default]
+    Truncate,
+    /// Continue the line below, indented
+    Wrap,
+}
+
+/// Columns of the terminal stdout is connected to. `COLUMNS` takes
+/// precedence; output that does not go to a terminal has no width limit.
+pub fn width() -> Option<usize> {
+    if let Some(columns) = std::env::var("COLUMNS")
+        .ok()
+        .and_then(|columns| columns.trim().parse::<usize>().ok())
+        .filter(|columns| *columns > 0)
+    {
+        return Some(columns);
+    }
+
+    terminal_size::terminal_size().map(|(terminal_size::Width(columns), _)| columns as usize)
+}
+
+/// Width of `text` on screen, excluding escape sequences. East Asian wide
+/// characters and emoji take two columns, combining marks none.
+pub fn visual_width(text: &str) -> usize {
+    tokens(text)
+        .iter()
+        .map(|token| match token {
+            Token::Text(_, width) => *width,
+            Token::Escape(_) => 0,
+        })
+        .sum()
+}
+
+/// `text` without any escape sequences
+pub fn strip_escapes(text: &str) -> String {
+    tokens(text)
+        .into_iter()
+        .filter_map(|token| match token {
+            Token::Text(grapheme, _) => Some(grapheme),
+            Token::Escape(_) => None,
+        })
+        .collect()
+}
+
+/// Splits `line` so that every part fits in `width` columns. Escapes are kept
+/// intact: colors and hyperlinks that span a line break are closed at the end
+/// of one part and resumed on the next.
+pub fn fit(line: &str, width: usize, overflow: Overflow) -> Vec<String> {
+    if visual_width(line) <= width || width == 0 {
+        return vec![line.to_string()];
+    }
+
+    match overflow {
+        Overflow::Truncate => vec![truncate(line, width)],
+        Overflow::Wrap => wrap(line, width),
+    }
+}
+
+fn truncate(line: &str, width: usize) -> String {
+    let mut output = String::new();
+    let mut columns = 0;
+    let mut cut = false;
+
+    for token in tokens(line) {
+        match token {
+            // Escapes after the cut still close colors and hyperlinks
+            Token::Escape(escape) => output.push_str(escape),
+            Token::Text(_, _) if cut => {}
+            // Leave one column for the ellipsis
+            Token::Text(_, grapheme_width) if columns + grapheme_width >= width => {
+                output.push(ELLIPSIS);
+                cut = true;
+            }
+            Token::Text(grapheme, grapheme_width) => {
+                output.push_str(grapheme);
+                columns += grapheme_width;
+            }
+        }
+    }
+
+    output
+}
+
+fn wrap(line: &str, width: usize) -> Vec<String> {
+    // Continuation lines are indented so they stand apart from new entries
+    let indent = if width > 8 { 2 } else { 0 };
+    let tokens = tokens(line);
+
+    let mut lines = Vec::new();
+    let mut start = 0;
+    // State at `start`, resumed on the next line: SGR sequences since the
+    // last reset and the open hyperlink
+    let mut active = String::new();
+    let mut link: Option<&str> = None;
+
+    loop {
+        let available = if lines.is_empty() {
+            width
+        } else {
+            width - indent
+        };
+
+        // Break before the first grapheme that does not fit, preferably at
+        // the last space so words stay whole
+        let mut end = tokens.len();
+        let mut columns = 0;
+        let mut last_space = None;
+        for (i, token) in tokens.iter().enumerate().skip(start) {
+            if let Token::Text(grapheme, grapheme_width) = token {
+                if columns + grapheme_width > available && columns > 0 {
+                    end = last_space.filter(|space| *space > start).unwrap_or(i);
+                    break;
+                }
+                if *grapheme == " " {
+                    last_space = Some(i);
+                }
+                columns += grapheme_width;
+            }
+        }
+
+        let mut current = String::new();
+        if !lines.is_empty() {
+            current.push_str(&" ".repeat(indent));
+            current.push_str(&active);
+            current.push_str(link.unwrap_or(""));
+        }
+        for token in &tokens[start..end] {
+            match token {
+                Token::Escape(escape) => {
+                    if *escape == "\x1b[0m" || *escape == "\x1b[m" {
+                        active.clear();
+                    } else if escape.starts_with("\x1b[") && escape.ends_with('m') {
+                        active.push_str(escape);
+                    } else if let Some(params) = escape.strip_prefix("\x1b]8;") {
+                        // `ESC ] 8 ; params ; URI ST`, an empty URI closes the link
+                        let uri = params
+                            .split_once(';')
+                            .map(|(_, uri)| uri.trim_end_matches(['\x07', '\\', '\x1b']))
+                            .unwrap_or("");
+                        link = (!uri.is_empty()).then_some(*escape);
+                    }
+                    current.push_str(escape);
+                }
+                Token::Text(grapheme, _) => current.push_str(grapheme),
+            }
+        }
+
+        if end == tokens.len() {
+            lines.push(current);
+            return lines;
+        }
+        if link.is_some() {
+            current.push_str("\x1b]8;;\x1b\\");
+        }
+        if !active.is_empty() {
+            current.push_str("\x1b[0m");
+        }
+        lines.push(current.trim_end().to_string());
+
+        start = end;
+        if matches!(tokens[start], Token::Text(" ", _)) {
+            start += 1;
+        }
+    }
+}
+
+enum Token<'a> {
+    /// A complete escape sequence, which takes no space on screen
+    Escape(&'a str),
+    /// A grapheme cluster and the columns it takes
+    Text(&'a str, usize),
+}
+
+/// Splits `text` into escape sequences and grapheme clusters
+fn tokens(text: &str) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    let mut rest = text;
+
+    while !rest.is_empty() {
+        let (plain, remainder) = rest.split_at(rest.find('\x1b').unwrap_or(rest.len()));
+        tokens.extend(
+            plain
+                .graphemes(true)
+                .map(|grapheme| Token::Text(grapheme, grapheme_width(grapheme))),
+        );
+        if remainder.is_empty() {
+            break;
+        }
+
+        let end = escape_len(remainder);
+        tokens.push(Token::Escape(&remainder[..end]));
+        rest = &remainder[end..];
+    }
+
+    tokens
+}
+
+/// Length of the escape sequence at the start of `text`, which begins with ESC
+fn escape_len(text: &str) -> usize {
+    let bytes = text.as_bytes();
+    match bytes.get(1) {
+        // CSI: parameter and intermediate bytes, up to the final byte
+        Some(b'[') => bytes[2..]
+            .iter()
+            .position(|b| (0x40..=0x7e).contains(b))
+            .map(|end| end + 3)
+            .unwrap_or(bytes.len()),
+        // OSC, DCS and friends: a string terminated by BEL or ESC \
+        Some(b']' | b'P' | b'X' | b'^' | b'_') => {
+            let mut i = 2;
+            while i < bytes.len() {
+                match bytes[i] {
+                    0x07 => return i + 1,
+                    0x1b if bytes.get(i + 1) == Some(&b'\\') => return i + 2,
+                    _ => i += 1,
+                }
+            }
+            bytes.len()
+        }
+        // Other sequences: intermediate bytes and a final byte. Anything
+        // outside printable ASCII ends it, so a stray ESC doesn't swallow
+        // part of a multi-byte character.
+        Some(_) => match bytes[1..].iter().position(|b| !(0x20..=0x2f).contains(b)) {
+            Some(end) if (0x30..=0x7e).contains(&bytes[end + 1]) => end + 2,
+            Some(_) => 1,
+            None => bytes.len(),
+        },
+        None => 1,
+    }
+}
+
+/// Columns a grapheme cluster takes, at most two. Control characters take
+/// no space.
+fn grapheme_width(grapheme: &str) -> usize {
+    if grapheme.chars().all(char::is_control) {
+        return 0;
+    }
+    // A variation selector 16 asks for the emoji (wide) presentation
+    if grapheme.contains('\u{fe0f}') {
+        return 2;
+    }
+    grapheme.width().min(2)
+}
@@ -0,0 +1,56 @@
+use super::TempDir;
+use crate::cache::ProbeCache;
+use serde_json::json;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+#[test]
+fn cache_entries_expire_and_follow_input_mtimes() {
+    let dir = TempDir::new("cache");
+
+    let status = dir.join("status");
+    fs::write(&status, "Package: bash\n").unwrap();
+    let inputs = vec![status.clone()];
+    let hour = Duration::from_secs(3600);
+
+    let cache = ProbeCache::in_dir(dir.join("cache"), false);
+    assert_eq!(cache.load("packages", hour, &inputs), None);
+
+    let value = json!([{ "manager": "dpkg", "count": 1 }]);
+    cache.store("packages", &inputs, &value);
+    assert_eq!(cache.load("packages", hour, &inputs), Some(value.clone()));
+
+    // --refresh ignores what is stored
+    let refreshing = ProbeCache::in_dir(dir.join("cache"), true);
+    assert_eq!(refreshing.load("packages", hour, &inputs), None);
+
+    // Touching the package database invalidates the entry
+    let later = fs::metadata(&status).unwrap().modified().unwrap() + Duration::from_secs(5);
+    fs::File::options()
+        .write(true)
+        .open(&status)
+        .unwrap()
+        .set_modified(later)
+        .unwrap();
+    assert_eq!(cache.load("packages", hour, &inputs), None);
+}
+
+#[test]
+fn cache_entries_are_kept_per_root() {
+    let dir = TempDir::new("cache-root");
+
+    let hour = Duration::from_secs(3600);
+    let host = ProbeCache::in_dir(dir.join("cache"), false).for_root(Path::new("/"));
+    let image = ProbeCache::in_dir(dir.join("cache"), false).for_root(&dir);
+
+    let value = json!([{ "manager": "pacman", "count": 3 }]);
+    host.store("packages", &[], &value);
+    assert_eq!(host.load("packages", hour, &[]), Some(value.clone()));
+    assert_eq!(image.load("packages", hour, &[]), None);
+
+    let other = json!([{ "manager": "apk", "count": 7 }]);
+    image.store("packages", &[], &other);
+    assert_eq!(image.load("packages", hour, &[]), Some(other));
+    assert_eq!(host.load("packages", hour, &[]), Some(value));
+}
@@ -0,0 +1,94 @@
+use crate::color::{quantize_escapes, ColorChoice, ColorDepth, ColorMode};
+use crate::terminal::strip_escapes;
+
+#[test]
+fn colors_parse_from_names_hex_rgb_and_indices() {
+    let cases = [
+        ("cyan", ColorChoice::Cyan),
+        ("Red", ColorChoice::Red),
+        ("#89b4fa", ColorChoice::Rgb(0x89, 0xb4, 0xfa)),
+        ("#FFF", ColorChoice::Rgb(255, 255, 255)),
+        ("rgb(137, 180, 250)", ColorChoice::Rgb(137, 180, 250)),
+        ("208", ColorChoice::Ansi256(208)),
+    ];
+    for (text, color) in cases {
+        assert_eq!(text.parse::<ColorChoice>(), Ok(color), "{}", text);
+    }
+
+    for text in ["purple", "#12345", "rgb(1, 2)", "256"] {
+        assert!(text.parse::<ColorChoice>().is_err(), "{}", text);
+    }
+
+    // Indices can also be written as bare TOML integers
+    let table: toml::Table = toml::from_str("color = 208").unwrap();
+    let color: ColorChoice = table["color"].clone().try_into().unwrap();
+    assert_eq!(color, ColorChoice::Ansi256(208));
+}
+
+#[test]
+fn color_depth_follows_colorterm_and_term() {
+    assert_eq!(
+        ColorDepth::from_env(Some("truecolor"), Some("xterm")),
+        ColorDepth::TrueColor
+    );
+    assert_eq!(
+        ColorDepth::from_env(None, Some("xterm-256color")),
+        ColorDepth::Ansi256
+    );
+    assert_eq!(
+        ColorDepth::from_env(None, Some("xterm-direct")),
+        ColorDepth::TrueColor
+    );
+    assert_eq!(ColorDepth::from_env(None, Some("linux")), ColorDepth::Basic);
+}
+
+#[test]
+fn colors_are_quantized_to_the_terminal_depth() {
+    let blue = ColorChoice::Rgb(0x89, 0xb4, 0xfa);
+    assert_eq!(blue.fg_params(ColorDepth::TrueColor), "38;2;137;180;250");
+    assert_eq!(blue.fg_params(ColorDepth::Ansi256), "38;5;111");
+    assert_eq!(blue.fg_params(ColorDepth::Basic), "94");
+
+    assert_eq!(
+        ColorChoice::Ansi256(208).fg_params(ColorDepth::Ansi256),
+        "38;5;208"
+    );
+    assert_eq!(ColorChoice::Ansi256(9).fg_params(ColorDepth::Basic), "91");
+    assert_eq!(ColorChoice::Cyan.fg_params(ColorDepth::TrueColor), "36");
+
+    // Gray values use the grayscale ramp
+    assert_eq!(
+        ColorChoice::Rgb(128, 128, 128).fg_params(ColorDepth::Ansi256),
+        "38;5;244"
+    );
+
+    let logo = "\x1b[38;2;230;230;230mMM\x1b[1;48;2;205;0;0m[\x1b[0m";
+    assert_eq!(quantize_escapes(logo, ColorDepth::TrueColor), logo);
+    assert_eq!(
+        quantize_escapes(logo, ColorDepth::Ansi256),
+        "\x1b[38;5;254mMM\x1b[1;48;5;160m[\x1b[0m"
+    );
+    assert_eq!(
+        quantize_escapes(logo, ColorDepth::Basic),
+        "\x1b[37mMM\x1b[1;41m[\x1b[0m"
+    );
+}
+
+#[test]
+fn color_mode_follows_flag_env_and_tty() {
+    assert!(ColorMode::Auto.resolve(None, None, true));
+    assert!(!ColorMode::Auto.resolve(None, None, false));
+    assert!(!ColorMode::Auto.resolve(Some("1"), None, true));
+    assert!(ColorMode::Auto.resolve(Some(""), None, true));
+    assert!(ColorMode::Auto.resolve(None, Some("1"), false));
+    assert!(!ColorMode::Auto.resolve(None, Some("0"), false));
+    assert!(!ColorMode::Auto.resolve(Some("1"), Some("1"), true));
+    assert!(ColorMode::Always.resolve(Some("1"), None, false));
+    assert!(!ColorMode::Never.resolve(None, Some("1"), true));
+}
+
+#[test]
+fn never_mode_strips_embedded_escapes() {
+    let logo = "\x1b[38;2;137;180;250m##\x1b[0m\x1b[1;5;38;5;208m@\x1b[2K\x1b[m.";
+    assert_eq!(strip_escapes(logo), "##@.");
+}
@@ -0,0 +1,145 @@
+use super::TempDir;
+use crate::config::Config;
+use crate::migrate::{ConfigMigrator, CONFIG_VERSION};
+use std::fs;
+
+#[test]
+fn partial_config_keeps_defaults_and_warns_about_unknown_keys() {
+    let contents = "redact_cpu_model = true\ncolour = \"red\"\n";
+    let (config, warnings) = Config::parse(contents).unwrap();
+
+    assert!(config.redact_cpu_model);
+    assert_eq!(config.modules, Config::default_modules());
+    assert_eq!(config.probe_timeout_ms, 2000);
+
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].position, Some((2, 1)));
+    assert_eq!(warnings[0].message, "unknown key `colour`");
+}
+
+#[test]
+fn parse_errors_report_line_and_column() {
+    let contents = "modules = [\"os\"]\nprobe_timeout_ms = \"fast\"\n";
+    let error = Config::parse(contents).unwrap_err();
+
+    assert_eq!(error.position, Some((2, 20)));
+    assert!(error
+        .to_string()
+        .starts_with("line 2, column 20: invalid type"));
+}
+
+#[test]
+fn v1_show_flags_migrate_to_modules_list() {
+    let contents =
+        "show_os = true\nshow_kernel = false\nshow_security = false\nredact_cpu_model = true\n";
+    let migration = ConfigMigrator::migrate(contents).unwrap();
+    assert_eq!((migration.from, migration.to), (1, CONFIG_VERSION));
+
+    let (config, warnings) = Config::parse(&migration.migrated).unwrap();
+    assert!(warnings.is_empty());
+    assert!(config.redact_cpu_model);
+    assert!(config.modules.contains(&"os".to_string()));
+    assert!(!config.modules.contains(&"kernel".to_string()));
+    assert!(!config.modules.contains(&"firewall".to_string()));
+    assert!(config.modules.contains(&"colors".to_string()));
+
+    assert!(migration.diff().contains("-show_kernel = false\n"));
+    assert!(ConfigMigrator::migrate(&migration.migrated).is_none());
+}
+
+#[test]
+fn command_line_overrides_use_toml_values_and_reject_unknown_keys() {
+    let mut config = Config::default();
+
+    config.set("probe_timeouts.gpu", "500").unwrap();
+    config.set("logo_color", "Red").unwrap();
+    config.set("modules", r#"["os", "cpu"]"#).unwrap();
+    assert_eq!(config.probe_timeout("gpu").as_millis(), 500);
+    assert_eq!(config.modules, ["os", "cpu"]);
+
+    assert!(config.set("logo_colr", "Red").is_err());
+    assert!(config.set("probe_timeout_ms", "fast").is_err());
+
+    config.show_module("kernel");
+    config.show_module("os");
+    config.hide_module("cpu");
+    assert_eq!(config.modules, ["os", "kernel"]);
+}
+
+#[test]
+fn layers_merge_key_by_key() {
+    let dir = TempDir::new("config");
+    fs::create_dir_all(dir.join("profiles")).unwrap();
+
+    let base = dir.join("config.toml");
+    fs::write(
+        &base,
+        "modules = [\"os\", \"kernel\"]\nredact_cpu_model = true\n[probe_timeouts]\ngpu = 100\n",
+    )
+    .unwrap();
+    fs::write(
+        dir.join("profiles/work.toml"),
+        "modules = [\"os\"]\n[probe_timeouts]\nnetwork = 300\n",
+    )
+    .unwrap();
+
+    let config = Config::load_from(&base, Some("work"));
+    assert_eq!(config.modules, ["os"]);
+    assert!(config.redact_cpu_model);
+    assert_eq!(config.probe_timeout("gpu").as_millis(), 100);
+    assert_eq!(config.probe_timeout("network").as_millis(), 300);
+    assert_eq!(config.probe_timeout_ms, 2000);
+
+    // Layers without legacy keys are not rewritten
+    assert!(!dir.join("profiles/work.toml.v1.bak").exists());
+}
+
+#[test]
+fn legacy_keys_in_profiles_are_not_migrated() {
+    let dir = TempDir::new("overlay");
+    fs::create_dir_all(dir.join("profiles")).unwrap();
+
+    let base = dir.join("config.toml");
+    fs::write(&base, "version = 3\nmodules = [\"os\", \"kernel\"]\n").unwrap();
+    let profile = dir.join("profiles/work.toml");
+    fs::write(&profile, "show_gpu = false\n").unwrap();
+
+    // The profile would otherwise become a full default list
+    let config = Config::load_from(&base, Some("work"));
+    assert_eq!(config.modules, ["os", "kernel"]);
+    assert_eq!(fs::read_to_string(&profile).unwrap(), "show_gpu = false\n");
+    assert!(!dir.join("profiles/work.toml.v1.bak").exists());
+}
+
+#[test]
+fn migration_keeps_comments_and_layout() {
+    let contents =
+        "# my settings\nshow_kernel = false # too long\n\n# privacy\nredact_cpu_model = true\n";
+    let migration = ConfigMigrator::migrate(contents).unwrap();
+
+    assert!(migration
+        .migrated
+        .starts_with("version = 3\n# my settings\nmodules = [\n"));
+    assert!(migration.migrated.contains("\n] # too long\n"));
+    assert!(migration
+        .migrated
+        .contains("\n# privacy\nredact_cpu_model = true\n"));
+    let (config, _) = Config::parse(&migration.migrated).unwrap();
+    assert!(!config.modules.contains(&"kernel".to_string()));
+}
+
+#[test]
+fn old_base_config_is_read_without_rewriting_it() {
+    let dir = TempDir::new("legacy");
+
+    let base = dir.join("config.toml");
+    let contents = "show_kernel = false\nshow_security = false\n";
+    fs::write(&base, contents).unwrap();
+
+    let config = Config::load_from(&base, None);
+    assert!(config.modules.contains(&"os".to_string()));
+    assert!(!config.modules.contains(&"kernel".to_string()));
+    assert!(!config.modules.contains(&"firewall".to_string()));
+    assert_eq!(fs::read_to_string(&base).unwrap(), contents);
+    assert!(!dir.join("config.toml.v1.bak").exists());
+}
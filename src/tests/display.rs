@@ -0,0 +1,165 @@
+use super::fixture;
+use crate::cache::ProbeCache;
+use crate::color::{self, ColorMode};
+use crate::config::Config;
+use crate::display::Display;
+use crate::modules::ModuleRegistry;
+use crate::sysinfo::SystemInfo;
+use crate::terminal::strip_escapes;
+use crate::theme::Theme;
+
+fn compose(settings: &[(&str, &str)], width: Option<usize>) -> Vec<String> {
+    let mut config = Config::default();
+    for (key, value) in settings {
+        config.set(key, value).unwrap();
+    }
+    let logo = ["/\\", "\\/"].map(String::from);
+    let info = ["user@host", "-------", "OS: Linux"].map(String::from);
+
+    Display::new(config, Theme::default())
+        .compose(&logo, &info, width)
+        .iter()
+        .map(|line| strip_escapes(line))
+        .collect()
+}
+
+#[test]
+fn logo_is_placed_by_position_gap_and_alignment() {
+    assert_eq!(
+        compose(&[("logo_gap", "2")], None),
+        ["/\\  user@host", "\\/  -------", "    OS: Linux"]
+    );
+    assert_eq!(
+        compose(
+            &[
+                ("logo_position", "right"),
+                ("logo_align", "bottom"),
+                ("logo_gap", "1")
+            ],
+            None
+        ),
+        ["user@host", "-------   /\\", "OS: Linux \\/"]
+    );
+    assert_eq!(
+        compose(&[("logo_position", "bottom"), ("logo_padding", "1")], None),
+        ["user@host", "-------", "OS: Linux", "", " /\\", " \\/"]
+    );
+    assert_eq!(
+        compose(&[("logo_position", "none")], None),
+        ["user@host", "-------", "OS: Linux"]
+    );
+}
+
+#[test]
+fn narrow_terminals_stack_or_drop_the_logo() {
+    let settings = [
+        ("stack_below_columns", "20"),
+        ("hide_logo_below_columns", "8"),
+    ];
+    assert_eq!(
+        compose(&settings, Some(16)),
+        ["/\\", "\\/", "", "user@host", "-------", "OS: Linux"]
+    );
+    assert_eq!(
+        compose(&settings, Some(7)),
+        ["user@h…", "-------", "OS: Li…"]
+    );
+}
+
+#[test]
+fn usage_bars_fill_by_ratio_and_color_by_threshold() {
+    let mut config = Config::default();
+    config.set("bar_fill", "#").unwrap();
+    config.set("bar_empty", "-").unwrap();
+    let display = Display::new(config, Theme::default());
+
+    assert_eq!(
+        strip_escapes(&display.format_bar(62, 100)),
+        "[######----] 62%"
+    );
+    assert_eq!(
+        strip_escapes(&display.format_bar(0, 100)),
+        "[----------] 0%"
+    );
+
+    // Green, yellow and red from the theme's palette
+    assert!(display.format_bar(50, 100).contains("\x1b[32m"));
+    assert!(display.format_bar(75, 100).contains("\x1b[33m"));
+    assert!(display.format_bar(95, 100).contains("\x1b[31m"));
+}
+
+#[test]
+fn temperatures_convert_and_color_near_critical() {
+    let display = Display::new(Config::default(), Theme::default());
+    assert_eq!(
+        strip_escapes(&display.format_temperature(45.4, None)),
+        "45°C"
+    );
+    // Value color, then yellow within 10 degrees of critical, then red
+    assert!(display
+        .format_temperature(80.0, Some(100.0))
+        .contains("\x1b[37m"));
+    assert!(display
+        .format_temperature(91.0, Some(100.0))
+        .contains("\x1b[33m"));
+    assert!(display
+        .format_temperature(100.0, Some(100.0))
+        .contains("\x1b[31m"));
+
+    let mut config = Config::default();
+    config.set("temperature_unit", "fahrenheit").unwrap();
+    let display = Display::new(config, Theme::default());
+    assert_eq!(
+        strip_escapes(&display.format_temperature(45.0, None)),
+        "113°F"
+    );
+}
+
+/// Full output with a logo carrying its own escapes and the color palette,
+/// colored as `mode` and the environment decide for a pipe
+fn piped_output(mode: ColorMode, no_color: Option<&str>, clicolor_force: Option<&str>) -> String {
+    color::set_enabled(mode.resolve(no_color, clicolor_force, false));
+
+    let registry = ModuleRegistry::new();
+    let modules = vec![
+        registry.get("kernel").unwrap(),
+        registry.get("colors").unwrap(),
+    ];
+    let info = SystemInfo::new(
+        &fixture("arch"),
+        &modules,
+        &Config::default(),
+        &ProbeCache::disabled(),
+    );
+    let display = Display::new(Config::default(), Theme::default());
+    let logo = ["\x1b[38;2;23;147;209m/\\\x1b[0m".to_string()];
+    let output = display
+        .compose(
+            &logo,
+            &display.format_system_info(&info, &modules, false),
+            None,
+        )
+        .join("\n");
+
+    color::set_enabled(true);
+    output
+}
+
+#[test]
+fn disabled_colors_strip_logo_escapes_and_skip_the_palette() {
+    for output in [
+        piped_output(ColorMode::Never, None, Some("1")),
+        piped_output(ColorMode::Auto, Some("1"), None),
+        piped_output(ColorMode::Auto, None, None),
+    ] {
+        assert!(!output.contains('\x1b'), "{:?}", output);
+        assert!(!output.contains("███"), "{:?}", output);
+        assert!(output.starts_with("/\\"), "{:?}", output);
+        assert!(output.contains("Kernel: 6.9.1-arch1-1"), "{:?}", output);
+    }
+
+    // CLICOLOR_FORCE colors a pipe
+    let output = piped_output(ColorMode::Auto, None, Some("1"));
+    assert!(output.contains("/\\\x1b[0m"), "{:?}", output);
+    assert!(output.contains("███"), "{:?}", output);
+}
@@ -0,0 +1,78 @@
+use super::fixture;
+use crate::cache::ProbeCache;
+use crate::config::Config;
+use crate::export::{JsonExporter, JSON_SCHEMA_VERSION};
+use crate::modules::ModuleRegistry;
+use crate::redactor::DataRedactor;
+use crate::sysinfo::SystemInfo;
+
+#[test]
+fn json_contains_only_enabled_modules() {
+    let registry = ModuleRegistry::new();
+    let modules = vec![
+        registry.get("kernel").unwrap(),
+        registry.get("firewall").unwrap(),
+        registry.get("updates").unwrap(),
+    ];
+    let info = SystemInfo::new(
+        &fixture("ubuntu"),
+        &modules,
+        &Config::default(),
+        &ProbeCache::disabled(),
+    );
+
+    let document = JsonExporter::export(&info, &modules, false);
+    assert_eq!(document["schema_version"], JSON_SCHEMA_VERSION);
+    assert_eq!(document["redacted"], false);
+    assert_eq!(document["hostname"], "ubuntu-desktop");
+    assert_eq!(document["kernel"], "6.5.0-28-generic");
+    assert!(document["security"].get("firewall_status").is_some());
+    assert!(document["security"].get("package_updates").is_some());
+    assert!(document["security"].get("ssh_status").is_none());
+    for key in ["os", "cpu", "memory", "network", "packages"] {
+        assert!(document.get(key).is_none(), "{}", key);
+    }
+
+    // Header keys first, then the modules in the order they are listed
+    let keys: Vec<_> = document.as_object().unwrap().keys().cloned().collect();
+    assert_eq!(
+        keys,
+        [
+            "schema_version",
+            "redacted",
+            "user",
+            "hostname",
+            "timeouts",
+            "kernel",
+            "security"
+        ]
+    );
+}
+
+#[test]
+fn screenshot_json_leaves_out_sensitive_modules() {
+    let registry = ModuleRegistry::new();
+    let modules = vec![
+        registry.get("firewall").unwrap(),
+        registry.get("ssh").unwrap(),
+        registry.get("updates").unwrap(),
+        registry.get("encryption").unwrap(),
+    ];
+    let config = Config::default();
+    let info = SystemInfo::new(
+        &fixture("ubuntu"),
+        &modules,
+        &config,
+        &ProbeCache::disabled(),
+    );
+    let redacted = DataRedactor::redact_system_info(&info, &config, &modules);
+
+    let document = JsonExporter::export(&redacted, &modules, true);
+    assert_eq!(document["schema_version"], JSON_SCHEMA_VERSION);
+    assert_eq!(document["redacted"], true);
+    let security = document["security"].as_object().unwrap();
+    assert!(security.get("firewall_status").is_none());
+    assert!(security.get("ssh_status").is_none());
+    assert!(security.get("package_updates").is_some());
+    assert!(security.get("disk_encryption").is_some());
+}
@@ -0,0 +1,56 @@
+use crate::config::Config;
+use crate::layout::{Segment, Template};
+use crate::modules::ModuleRegistry;
+
+fn field(name: &str, required: bool, fallback: Option<&str>) -> Segment {
+    Segment::Field {
+        name: name.to_string(),
+        required,
+        fallback: fallback.map(str::to_string),
+    }
+}
+
+#[test]
+fn templates_parse_labels_fields_and_literals() {
+    let template =
+        Template::parse("{label:OS} {os.name} {os.version?} ({gpu|none}) {{x}}").unwrap();
+
+    assert_eq!(
+        template.segments,
+        vec![
+            Segment::Label("OS".to_string()),
+            Segment::Text(" ".to_string()),
+            field("os.name", false, None),
+            Segment::Text(" ".to_string()),
+            field("os.version", true, None),
+            Segment::Text(" (".to_string()),
+            field("gpu", false, Some("none")),
+            Segment::Text(") {x}".to_string()),
+        ]
+    );
+
+    assert!(Template::parse("{os.name").is_err());
+    assert!(Template::parse("os}").is_err());
+    assert!(Template::parse("{os name}").is_err());
+}
+
+#[test]
+fn layout_limits_collected_modules() {
+    let mut config = Config::default();
+    config
+        .set(
+            "layout",
+            r#"[{ lines = ["{label:OS} {os.name}", "{security.ssh_status}", "{memory?}"] }]"#,
+        )
+        .unwrap();
+
+    let registry = ModuleRegistry::new();
+    let names: Vec<&str> = registry
+        .enabled(&config)
+        .iter()
+        .map(|module| module.name())
+        .collect();
+    assert_eq!(names, ["os", "memory", "ssh"]);
+
+    assert!(config.set("layout", r#"[{ lines = ["{os"] }]"#).is_err());
+}
@@ -0,0 +1,179 @@
+//! Fixture-driven probe tests.
+//!
+//! Every directory under `tests/fixtures` describes one machine: `root/` is
+//! used as the sysroot for `/proc`, `/sys` and `/etc` reads, and
+//! `commands.toml` holds the recorded output of the external commands the
+//! probes run. Commands missing from the recording behave as if the program
+//! is not installed.
+
+mod cache;
+mod color;
+mod config;
+mod display;
+mod export;
+mod layout;
+mod probes;
+mod terminal;
+mod theme;
+
+use crate::probe::{CommandRunner, ProbeContext};
+use serde::Deserialize;
+use std::fs;
+use std::io;
+use std::ops::Deref;
+use std::os::unix::process::ExitStatusExt;
+use std::path::{Path, PathBuf};
+use std::process::{ExitStatus, Output};
+use std::sync::Arc;
+use std::time::Duration;
+
+#[derive(Deserialize)]
+struct Recording {
+    #[serde(default)]
+    commands: Vec<RecordedCommand>,
+}
+
+#[derive(Deserialize, Clone)]
+pub struct RecordedCommand {
+    /// Program and arguments separated by single spaces
+    command: String,
+    #[serde(default)]
+    status: i32,
+    #[serde(default)]
+    stdout: String,
+    #[serde(default)]
+    stderr: String,
+    /// Simulates a command that never finishes
+    #[serde(default)]
+    timeout: bool,
+}
+
+impl RecordedCommand {
+    /// A command that succeeds without output
+    pub fn new(command: &str) -> Self {
+        Self {
+            command: command.to_string(),
+            status: 0,
+            stdout: String::new(),
+            stderr: String::new(),
+            timeout: false,
+        }
+    }
+
+    pub fn status(mut self, status: i32) -> Self {
+        self.status = status;
+        self
+    }
+
+    pub fn stdout(mut self, stdout: &str) -> Self {
+        self.stdout = stdout.to_string();
+        self
+    }
+
+    pub fn timeout(mut self) -> Self {
+        self.timeout = true;
+        self
+    }
+}
+
+/// Replays the commands recorded in a fixture's `commands.toml`
+pub struct FixtureRunner {
+    commands: Vec<RecordedCommand>,
+}
+
+impl CommandRunner for FixtureRunner {
+    fn run(&self, program: &str, args: &[&str], timeout: Duration) -> io::Result<Output> {
+        let command_line = std::iter::once(program)
+            .chain(args.iter().copied())
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        let Some(recorded) = self.commands.iter().find(|c| c.command == command_line) else {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("'{}' is not recorded", command_line),
+            ));
+        };
+
+        if recorded.timeout {
+            return Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                format!("'{}' timed out after {:?}", command_line, timeout),
+            ));
+        }
+
+        Ok(Output {
+            status: ExitStatus::from_raw(recorded.status << 8),
+            stdout: recorded.stdout.clone().into_bytes(),
+            stderr: recorded.stderr.clone().into_bytes(),
+        })
+    }
+}
+
+pub fn fixture_dir(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join(name)
+}
+
+/// Probe context that reads files and command output from the named fixture
+pub fn fixture(name: &str) -> ProbeContext {
+    let recording = fs::read_to_string(fixture_dir(name).join("commands.toml"))
+        .map(|contents| toml::from_str::<Recording>(&contents).expect("valid commands.toml"))
+        .unwrap_or(Recording {
+            commands: Vec::new(),
+        });
+
+    fixture_with(name, recording.commands)
+}
+
+/// Probe context for the named fixture's files that replays `commands`
+/// instead of its recording
+pub fn fixture_with(name: &str, commands: Vec<RecordedCommand>) -> ProbeContext {
+    replaying(fixture_dir(name).join("root"), commands)
+}
+
+/// Probe context for `root` that replays `commands`
+pub fn replaying(root: impl Into<PathBuf>, commands: Vec<RecordedCommand>) -> ProbeContext {
+    ProbeContext::new(
+        Arc::new(FixtureRunner { commands }),
+        root,
+        Duration::from_secs(1),
+    )
+}
+
+/// An empty directory under the system's temporary directory, removed again
+/// when dropped
+pub struct TempDir(PathBuf);
+
+impl TempDir {
+    /// `name` keeps the directories of tests running in parallel apart
+    pub fn new(name: &str) -> Self {
+        let path =
+            std::env::temp_dir().join(format!("hxfetch-{}-test-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).expect("temporary directory");
+        Self(path)
+    }
+}
+
+impl Deref for TempDir {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl AsRef<Path> for TempDir {
+    fn as_ref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
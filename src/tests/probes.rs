@@ -0,0 +1,755 @@
+use super::{fixture, fixture_with, replaying, RecordedCommand, TempDir};
+use crate::cache::ProbeCache;
+use crate::config::Config;
+use crate::display::Display;
+use crate::modules::{field_owners, ModuleRegistry};
+use crate::pci::PciIds;
+use crate::probe::{CommandRunner, SystemRunner};
+use crate::redactor::DataRedactor;
+use crate::security::SecurityInfo;
+use crate::sysinfo::{MemoryInfo, SystemInfo};
+use std::io;
+use std::path::Path;
+use std::time::Duration;
+
+#[test]
+fn os_and_kernel_from_sysroot() {
+    let cases = [
+        ("ubuntu", "Ubuntu 22.04.4 LTS", "22.04", "6.5.0-28-generic"),
+        (
+            "fedora",
+            "Fedora Linux 40 (Workstation Edition)",
+            "40",
+            "6.8.9-300.fc40.x86_64",
+        ),
+        ("arch", "Arch Linux", "rolling", "6.9.1-arch1-1"),
+        ("alpine", "Alpine Linux v3.19", "3.19.1", "6.6.31-0-lts"),
+    ];
+
+    for (name, os, version, kernel) in cases {
+        let ctx = fixture(name);
+        let info = SystemInfo::get_linux_os_info(&ctx);
+        assert_eq!(info.pretty_name, os, "{}", name);
+        assert_eq!(info.version, version, "{}", name);
+        assert_eq!(SystemInfo::get_kernel_version(&ctx), kernel, "{}", name);
+    }
+}
+
+#[test]
+fn hostname_from_kernel() {
+    assert_eq!(
+        SystemInfo::get_hostname(&fixture("ubuntu")),
+        "ubuntu-desktop"
+    );
+    assert_eq!(SystemInfo::get_hostname(&fixture("arch")), "archbox");
+    // Containers often ship without /etc/hostname
+    assert_eq!(
+        SystemInfo::get_hostname(&fixture("alpine")),
+        "alpine-container"
+    );
+}
+
+#[test]
+fn os_for_logo_without_os_module() {
+    let ctx = fixture("arch");
+    let registry = ModuleRegistry::new();
+    let modules = vec![registry.get("kernel").unwrap()];
+    let info = SystemInfo::new(&ctx, &modules, &Config::default(), &ProbeCache::disabled());
+
+    assert_eq!(info.os.pretty_name, "Arch Linux");
+    assert_eq!(info.hostname, "archbox");
+}
+
+#[test]
+fn fqdn_from_etc_hosts() {
+    assert_eq!(
+        SystemInfo::get_fqdn(&fixture("ubuntu"), "ubuntu-desktop").as_deref(),
+        Some("ubuntu-desktop.home.arpa")
+    );
+    // Only localhost entries
+    assert_eq!(SystemInfo::get_fqdn(&fixture("arch"), "archbox"), None);
+    assert_eq!(
+        SystemInfo::get_fqdn(&fixture("alpine"), "alpine-container"),
+        None
+    );
+}
+
+#[test]
+fn effective_user_from_passwd() {
+    assert_eq!(
+        SystemInfo::get_effective_user(&fixture("fedora")).as_deref(),
+        Some("sam")
+    );
+    assert_eq!(
+        SystemInfo::get_effective_user(&fixture("alpine")).as_deref(),
+        Some("root")
+    );
+    assert_eq!(SystemInfo::get_effective_user(&fixture("arch")), None);
+}
+
+#[test]
+fn user_falls_back_from_env_to_passwd() {
+    let user = |name: &str, vars: &[(&str, &str)]| {
+        SystemInfo::user_from_env(&fixture(name), |var| {
+            vars.iter()
+                .find(|(name, _)| *name == var)
+                .map(|(_, value)| value.to_string())
+        })
+    };
+
+    let all = [("SUDO_USER", "alex"), ("USER", "root"), ("LOGNAME", "lee")];
+    assert_eq!(user("fedora", &all), "alex");
+    assert_eq!(user("fedora", &all[1..]), "root");
+    assert_eq!(user("fedora", &all[2..]), "lee");
+    // Empty values are skipped like missing ones
+    assert_eq!(user("fedora", &[("USER", ""), ("LOGNAME", "lee")]), "lee");
+    assert_eq!(user("fedora", &[("USER", "")]), "sam");
+    assert_eq!(user("alpine", &[]), "root");
+    assert_eq!(user("arch", &[]), "Unknown");
+}
+
+#[test]
+fn firewall_backends() {
+    assert_eq!(
+        SecurityInfo::get_firewall_status(&fixture("ubuntu")),
+        "UFW: Active"
+    );
+    assert_eq!(
+        SecurityInfo::get_firewall_status(&fixture("fedora")),
+        "firewalld: Active"
+    );
+    assert_eq!(
+        SecurityInfo::get_firewall_status(&fixture("arch")),
+        "iptables: 3 rules"
+    );
+    assert_eq!(
+        SecurityInfo::get_firewall_status(&fixture("alpine")),
+        "None detected"
+    );
+}
+
+#[test]
+fn mandatory_access_control() {
+    assert_eq!(
+        SecurityInfo::get_selinux_status(&fixture("fedora")),
+        "Enforcing"
+    );
+    assert_eq!(
+        SecurityInfo::get_selinux_status(&fixture("ubuntu")),
+        "Not available"
+    );
+    assert_eq!(
+        SecurityInfo::get_apparmor_status(&fixture("ubuntu")),
+        "Active (5 profiles)"
+    );
+    assert_eq!(
+        SecurityInfo::get_apparmor_status(&fixture("fedora")),
+        "Not available"
+    );
+}
+
+#[test]
+fn disk_encryption_from_lsblk() {
+    assert_eq!(
+        SecurityInfo::get_disk_encryption_status(&fixture("ubuntu")),
+        "LUKS (1 encrypted)"
+    );
+    assert_eq!(
+        SecurityInfo::get_disk_encryption_status(&fixture("fedora")),
+        "None detected"
+    );
+}
+
+#[test]
+fn secure_boot_from_efivars() {
+    assert_eq!(
+        SecurityInfo::get_secure_boot_status(&fixture("fedora")),
+        "Enabled"
+    );
+    assert_eq!(
+        SecurityInfo::get_secure_boot_status(&fixture("arch")),
+        "Unknown"
+    );
+}
+
+#[test]
+fn ssh_and_fail2ban_services() {
+    assert_eq!(SecurityInfo::get_ssh_status(&fixture("ubuntu")), "Running");
+    assert_eq!(SecurityInfo::get_ssh_status(&fixture("fedora")), "Stopped");
+    // No systemd, falls back to looking for a listener on port 22
+    assert_eq!(
+        SecurityInfo::get_ssh_status(&fixture("alpine")),
+        "Running (port 22)"
+    );
+
+    assert_eq!(
+        SecurityInfo::get_fail2ban_status(&fixture("arch")),
+        "Active (2 jails)"
+    );
+    assert_eq!(
+        SecurityInfo::get_fail2ban_status(&fixture("alpine")),
+        "Not installed"
+    );
+}
+
+#[test]
+fn package_updates() {
+    // Two from jammy-security, plus a Flatpak runtime with a newer commit
+    assert_eq!(
+        SecurityInfo::get_package_updates(&fixture("ubuntu")),
+        "4 available (2 security)"
+    );
+    assert_eq!(
+        SecurityInfo::get_package_updates(&fixture("fedora")),
+        "2 available (1 security)"
+    );
+    assert_eq!(
+        SecurityInfo::get_package_updates(&fixture("arch")),
+        "2 available"
+    );
+    assert_eq!(
+        SecurityInfo::get_package_updates(&fixture("alpine")),
+        "1 available"
+    );
+    // New dependencies pulled in by an update don't count
+    assert_eq!(
+        SecurityInfo::get_package_updates(&fixture("void")),
+        "2 available"
+    );
+}
+
+#[test]
+fn package_updates_from_other_managers() {
+    // A root holding only the given package databases, with a user whose
+    // home is /home/user
+    let updates = |databases: &[&str], commands: &[RecordedCommand]| {
+        let root = TempDir::new("updates");
+        for database in databases {
+            std::fs::create_dir_all(root.join(database.trim_start_matches('/'))).unwrap();
+        }
+        let ctx = replaying(root.to_path_buf(), commands.to_vec());
+        SecurityInfo::package_updates(&ctx, Some(Path::new("/home/user")))
+    };
+
+    let zypper_updates = "Loading repository data...
+Reading installed packages...
+S | Repository          | Name    | Current Version | Available Version | Arch
+--+---------------------+---------+-----------------+-------------------+-------
+v | Main Update Repo    | curl    | 8.6.0-1.1       | 8.6.0-2.1         | x86_64
+v | Main Update Repo    | vim     | 9.1.0-1.1       | 9.1.0330-1.1      | x86_64
+";
+    let zypper_patches = "Repository | Name              | Category | Severity  | Interactive | Status  | Summary
+-----------+-------------------+----------+-----------+-------------+---------+--------
+Update     | openSUSE-2024-101 | security | important | ---         | needed  | Security update for curl
+Update     | openSUSE-2024-099 | security | moderate  | ---         | applied | Security update for glibc
+";
+    assert_eq!(
+        updates(
+            &["/etc/zypp"],
+            &[
+                RecordedCommand::new("zypper --non-interactive --no-refresh list-updates")
+                    .stdout(zypper_updates),
+                RecordedCommand::new(
+                    "zypper --non-interactive --no-refresh list-patches --category security"
+                )
+                .stdout(zypper_patches),
+            ]
+        ),
+        "2 available (1 security)"
+    );
+
+    assert_eq!(
+        updates(
+            &["/var/db/xbps"],
+            &[RecordedCommand::new("xbps-install -un").stdout(
+                "curl-8.7.1_1 update x86_64 https://repo-default.voidlinux.org/current 1 2\n"
+            )]
+        ),
+        "1 available"
+    );
+
+    let ostree = r#"{"deployments": [], "cached-update": {"version": "40.20240520.0",
+        "rpm-diff": {"upgraded": [["curl", [], []], ["kernel", [], []]], "added": []}}}"#;
+    assert_eq!(
+        updates(
+            &["/run/ostree-booted"],
+            &[RecordedCommand::new("rpm-ostree status --json").stdout(ostree)]
+        ),
+        "2 available"
+    );
+    assert_eq!(
+        updates(
+            &["/run/ostree-booted"],
+            &[RecordedCommand::new("rpm-ostree status --json")
+                .stdout(r#"{"deployments": [], "cached-update": null}"#)]
+        ),
+        "Up to date"
+    );
+
+    // pacman -Qu exits with 1 when there is nothing to list
+    assert_eq!(
+        updates(
+            &["/var/lib/pacman/local"],
+            &[RecordedCommand::new("pacman -Qu").status(1)]
+        ),
+        "Up to date"
+    );
+    assert_eq!(updates(&["/var/lib/pacman/local"], &[]), "Unknown");
+
+    // The distribution's own database picks the backend, not whichever
+    // package manager happens to be installed as well
+    let both = [
+        RecordedCommand::new("apt list --upgradable").stdout(
+            "curl/jammy-updates 7.81.0-1ubuntu1.16 amd64 [upgradable from: 7.81.0-1ubuntu1.15]\n",
+        ),
+        RecordedCommand::new("pacman -Qu")
+            .stdout("linux 6.9.1.arch1-1 -> 6.9.2.arch1-1\nvim 9.1.0-1 -> 9.1.1-1\n"),
+    ];
+    assert_eq!(updates(&["/var/lib/pacman/local"], &both), "2 available");
+    assert_eq!(updates(&["/var/lib/dpkg/status"], &both), "1 available");
+
+    // Flatpak is only asked where it has a system or user installation
+    let flatpak = [
+        RecordedCommand::new("pacman -Qu").status(1),
+        RecordedCommand::new("flatpak list --columns=application,active,latest")
+            .stdout("org.gnome.Platform\t1d9e7f0c2b3a\t5a6b7c8d9e0f\n"),
+    ];
+    assert_eq!(updates(&["/var/lib/pacman/local"], &flatpak), "Up to date");
+    assert_eq!(
+        updates(&["/var/lib/pacman/local", "/var/lib/flatpak"], &flatpak),
+        "1 available"
+    );
+    assert_eq!(
+        updates(
+            &["/var/lib/pacman/local", "/home/user/.local/share/flatpak"],
+            &flatpak
+        ),
+        "1 available"
+    );
+}
+
+#[test]
+fn gpus_from_pci_bus() {
+    // Names from the embedded database
+    let intel = SystemInfo::get_gpu_info(&fixture("arch"));
+    assert_eq!(intel.len(), 1);
+    assert_eq!(intel[0].name, "Intel UHD Graphics 620");
+    assert_eq!(intel[0].driver.as_deref(), Some("i915"));
+    assert_eq!(intel[0].vram_bytes, None);
+
+    let amd = SystemInfo::get_gpu_info(&fixture("fedora"));
+    assert_eq!(amd[0].name, "AMD Radeon RX 6600/6600 XT/6600M");
+    assert_eq!(amd[0].vendor, "AMD");
+    assert_eq!(amd[0].pci_address, "0000:03:00.0");
+    assert_eq!(amd[0].vram_bytes, Some(8573157376));
+    assert_eq!(
+        Display::format_gpu(&amd[0]),
+        "AMD Radeon RX 6600/6600 XT/6600M (amdgpu, 8.0 GB)"
+    );
+
+    // Hybrid laptop, the NVIDIA card is only in the system's pci.ids
+    let hybrid = SystemInfo::get_gpu_info(&fixture("ubuntu"));
+    let names: Vec<&str> = hybrid.iter().map(|gpu| gpu.name.as_str()).collect();
+    assert_eq!(
+        names,
+        ["Intel Iris Xe Graphics", "NVIDIA GeForce RTX 3050 Mobile"]
+    );
+    assert_eq!(hybrid[1].driver.as_deref(), Some("nvidia"));
+
+    assert!(SystemInfo::get_gpu_info(&fixture("alpine")).is_empty());
+}
+
+#[test]
+fn pci_ids_lookup() {
+    let database = "1002  Advanced Micro Devices, Inc. [AMD/ATI]\n\t73ff  Navi 23\n\t\t1043 05e3  Dual\n1af4  Red Hat, Inc.\n";
+    assert_eq!(
+        PciIds::lookup(database, 0x1002, 0x73ff),
+        (
+            Some("Advanced Micro Devices, Inc. [AMD/ATI]".to_string()),
+            Some("Navi 23".to_string())
+        )
+    );
+    // Subsystem IDs and other vendors' devices don't match
+    assert_eq!(PciIds::lookup(database, 0x1002, 0x1043).1, None);
+    assert_eq!(PciIds::lookup(database, 0x1af4, 0x73ff).1, None);
+
+    let ids = PciIds::load(&fixture("alpine"));
+    assert_eq!(ids.name(0x1af4, 0x1050).0, "Red Hat Virtio 1.0 GPU");
+    assert_eq!(ids.name(0x10de, 0xffff).0, "NVIDIA device ffff");
+    assert_eq!(ids.name(0xabcd, 0x0001).0, "abcd device 0001");
+}
+
+#[test]
+fn sensors_from_hwmon() {
+    let sensors = SystemInfo::get_sensor_info(&fixture("ubuntu"));
+    let names: Vec<String> = sensors.temperatures.iter().map(|t| t.name()).collect();
+    assert_eq!(
+        names,
+        [
+            "acpitz/temp1",
+            "coretemp/Package id 0",
+            "coretemp/Core 0",
+            "nvme/Composite"
+        ]
+    );
+    assert_eq!(sensors.temperatures[1].celsius, 62.0);
+    assert_eq!(sensors.temperatures[3].critical, Some(84.85));
+    let fans: Vec<(String, u32)> = sensors.fans.iter().map(|f| (f.name(), f.rpm)).collect();
+    assert_eq!(
+        fans,
+        [
+            ("thinkpad/fan1".to_string(), 2650),
+            ("thinkpad/fan2".to_string(), 0)
+        ]
+    );
+
+    // hwmon10 sorts after hwmon2
+    let sensors = SystemInfo::get_sensor_info(&fixture("fedora"));
+    let chips: Vec<&str> = sensors.fans.iter().map(|f| f.chip.as_str()).collect();
+    assert_eq!(chips, ["amdgpu", "nct6798"]);
+    assert_eq!(sensors.fans[1].label, "CPU Fan");
+
+    // Thermal zones without hwmon, thermal_zone0 reads 0 and is skipped
+    let zones = SystemInfo::get_sensor_info(&fixture("arch"));
+    assert_eq!(zones.temperatures.len(), 1);
+    assert_eq!(zones.temperatures[0].name(), "x86_pkg_temp/thermal_zone1");
+    assert_eq!(zones.temperatures[0].celsius, 38.0);
+
+    let none = SystemInfo::get_sensor_info(&fixture("alpine"));
+    assert!(none.temperatures.is_empty() && none.fans.is_empty());
+}
+
+#[test]
+fn sensor_selection() {
+    let all = SystemInfo::get_sensor_info(&fixture("fedora"));
+
+    // One temperature per chip by default
+    let mut sensors = all.clone();
+    SystemInfo::filter_sensors(&mut sensors, &Config::default());
+    let names: Vec<String> = sensors.temperatures.iter().map(|t| t.name()).collect();
+    assert_eq!(names, ["k10temp/Tctl", "amdgpu/edge", "nct6798/SYSTIN"]);
+    assert_eq!(sensors.fans.len(), 2);
+
+    let mut config = Config::default();
+    config
+        .set("temperature_sensors", r#"["amdgpu/*"]"#)
+        .unwrap();
+    config.set("fan_sensors", r#"["*/CPU*"]"#).unwrap();
+    let mut sensors = all;
+    SystemInfo::filter_sensors(&mut sensors, &config);
+    let names: Vec<String> = sensors.temperatures.iter().map(|t| t.name()).collect();
+    assert_eq!(names, ["amdgpu/edge", "amdgpu/junction"]);
+    assert_eq!(sensors.fans[0].name(), "nct6798/CPU Fan");
+    assert_eq!(sensors.fans.len(), 1);
+}
+
+#[test]
+fn network_from_proc_and_sys() {
+    let network = SystemInfo::get_network_info(&fixture("ubuntu")).expect("network");
+    assert_eq!(network.interface, "wlp0s20f3");
+    assert_eq!(network.addresses, ["192.168.1.42", "2001:db8:1::42"]);
+
+    let names: Vec<&str> = network.interfaces.iter().map(|i| i.name.as_str()).collect();
+    assert_eq!(names, ["enp0s31f6", "wlp0s20f3"]);
+    let wifi = &network.interfaces[1];
+    assert!(wifi.wireless);
+    assert_eq!(wifi.state, "up");
+    assert_eq!(wifi.mac.as_deref(), Some("3c:a9:f4:12:34:56"));
+    assert_eq!(network.interfaces[0].speed_mbps, None);
+
+    let network = SystemInfo::get_network_info(&fixture("fedora")).expect("network");
+    assert_eq!(network.interface, "enp5s0");
+    assert_eq!(network.addresses, ["10.0.0.23"]);
+    assert_eq!(network.interfaces[1].speed_mbps, Some(1000));
+    // Addresses are assigned by the most specific route
+    assert_eq!(network.interfaces[0].ipv4, ["172.17.0.1"]);
+
+    let network = SystemInfo::get_network_info(&fixture("alpine")).expect("network");
+    assert_eq!(network.interface, "eth0");
+    assert_eq!(network.addresses, ["172.17.0.2"]);
+}
+
+#[test]
+fn network_filters_and_redaction() {
+    let mut network = SystemInfo::get_network_info(&fixture("fedora"));
+    let mut config = Config::default();
+    config.set("network_show_down", "true").unwrap();
+    config.set("network_exclude", r#"["docker*"]"#).unwrap();
+    SystemInfo::filter_network(&mut network, &config);
+    let network = network.unwrap();
+    assert_eq!(network.interfaces.len(), 1);
+
+    // Filtering out the default route's interface picks another primary one
+    let mut network = SystemInfo::get_network_info(&fixture("ubuntu"));
+    config.set("network_exclude", r#"["wlp*"]"#).unwrap();
+    SystemInfo::filter_network(&mut network, &config);
+    let network = network.unwrap();
+    assert_eq!(network.interface, "enp0s31f6");
+    assert_eq!(network.addresses, network.interfaces[0].ipv4);
+
+    // Nothing is left once down links are hidden again
+    let mut network = SystemInfo::get_network_info(&fixture("ubuntu"));
+    config.set("network_show_down", "false").unwrap();
+    SystemInfo::filter_network(&mut network, &config);
+    assert!(network.is_none());
+
+    let redacted =
+        DataRedactor::redact_network(&SystemInfo::get_network_info(&fixture("ubuntu"))).unwrap();
+    assert_eq!(redacted.addresses, ["XXX.XXX.XXX.XXX", "XXXX:XXXX::XXXX"]);
+    assert_eq!(
+        redacted.interfaces[1].mac.as_deref(),
+        Some("XX:XX:XX:XX:XX:XX")
+    );
+    assert_eq!(redacted.interface, "wlp0s20f3");
+
+    assert_eq!(
+        DataRedactor::redact_interface_name("enx001122334455"),
+        "enxXXXXXXXXXXXX"
+    );
+    assert_eq!(
+        DataRedactor::redact_interface_name("wlx3ca9f4123456"),
+        "wlxXXXXXXXXXXXX"
+    );
+    assert_eq!(DataRedactor::redact_interface_name("enp5s0"), "enp5s0");
+}
+
+#[test]
+fn package_counts() {
+    let counts = |name: &str, home: Option<&str>| -> Vec<(String, usize)> {
+        SystemInfo::count_packages(&fixture(name), home.map(Path::new))
+            .into_iter()
+            .map(|package| (package.manager, package.count))
+            .collect()
+    };
+    let expected = |counts: &[(&str, usize)]| -> Vec<(String, usize)> {
+        counts
+            .iter()
+            .map(|(manager, count)| (manager.to_string(), *count))
+            .collect()
+    };
+
+    // Every manager is listed; removed packages with leftover config files
+    // don't count
+    assert_eq!(
+        counts("ubuntu", Some("/home/alex")),
+        expected(&[
+            ("dpkg", 4),
+            ("nix-user", 2),
+            ("flatpak", 2),
+            ("snap", 3),
+            ("cargo", 2),
+            ("pip", 2)
+        ])
+    );
+    assert_eq!(
+        counts("ubuntu", None),
+        expected(&[("dpkg", 4), ("flatpak", 2), ("snap", 3)])
+    );
+    assert_eq!(
+        counts("fedora", None),
+        expected(&[("rpm", 5), ("flatpak", 3)])
+    );
+    assert_eq!(counts("arch", None), expected(&[("pacman", 4)]));
+    assert_eq!(counts("alpine", None), expected(&[("apk", 3)]));
+    assert_eq!(counts("void", None), expected(&[("xbps", 4)]));
+
+    // Commands are not run for managers without a database
+    let ctx = fixture_with(
+        "arch",
+        vec![
+            RecordedCommand::new("rpm -qa").stdout("foo\nbar\n"),
+            RecordedCommand::new("brew list").stdout("foo\nbar\n"),
+        ],
+    );
+    let managers: Vec<String> = SystemInfo::count_packages(&ctx, None)
+        .into_iter()
+        .map(|package| package.manager)
+        .collect();
+    assert_eq!(managers, ["pacman"]);
+}
+
+#[test]
+fn timed_out_module_falls_back_to_unknown() {
+    let ctx = fixture_with(
+        "ubuntu",
+        vec![RecordedCommand::new("apt list --upgradable").timeout()],
+    );
+
+    let registry = ModuleRegistry::new();
+    let modules = vec![
+        registry.get("updates").unwrap(),
+        registry.get("kernel").unwrap(),
+    ];
+    let info = SystemInfo::new(&ctx, &modules, &Config::default(), &ProbeCache::disabled());
+
+    assert_eq!(info.security.package_updates, "Unknown");
+    assert_eq!(info.kernel, "6.5.0-28-generic");
+    assert_eq!(info.timeouts.len(), 1);
+    assert_eq!(info.timeouts[0].module, "updates");
+    assert_eq!(info.timeouts[0].commands, vec!["apt list --upgradable"]);
+}
+
+#[test]
+fn timed_out_command_keeps_the_rest_of_the_module() {
+    let ctx = fixture_with(
+        "ubuntu",
+        vec![
+            RecordedCommand::new("apt list --upgradable").stdout(
+                "libssl3/jammy-security 3.0.2-0ubuntu1.15 amd64 [upgradable from: 3.0.2-0ubuntu1.14]\n",
+            ),
+            RecordedCommand::new("flatpak list --columns=application,active,latest").timeout(),
+        ],
+    );
+
+    let registry = ModuleRegistry::new();
+    let modules = vec![registry.get("updates").unwrap()];
+    let info = SystemInfo::new(&ctx, &modules, &Config::default(), &ProbeCache::disabled());
+
+    // The apt count survives the hanging flatpak
+    assert_eq!(info.security.package_updates, "1 available (1 security)");
+    assert_eq!(info.timeouts.len(), 1);
+    assert_eq!(
+        info.timeouts[0].commands,
+        vec!["flatpak list --columns=application,active,latest"]
+    );
+}
+
+#[test]
+fn system_runner_kills_slow_commands() {
+    let result = SystemRunner.run("sleep", &["5"], Duration::from_millis(50));
+    assert_eq!(result.unwrap_err().kind(), io::ErrorKind::TimedOut);
+
+    let output = SystemRunner
+        .run("echo", &["hello"], Duration::from_secs(5))
+        .expect("echo runs");
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "hello\n");
+}
+
+#[test]
+fn storage_counts_each_device_once() {
+    let contents = fixture("fedora")
+        .read_to_string("/proc/self/mountinfo")
+        .unwrap();
+    let mounts = SystemInfo::parse_mountinfo(&contents);
+    assert_eq!(mounts[2].device, "/dev/nvme0n1p3");
+    assert_eq!(mounts[10].mount_point, "/mnt/nas backup");
+    assert_eq!(mounts[10].device, "0:45");
+
+    let gib = 1 << 30;
+    let storage = SystemInfo::storage_from_mounts(&mounts, |mount_point| match mount_point {
+        "/" | "/home" | "/srv/media" => Some((400 * gib, 100 * gib)),
+        "/boot" => Some((gib, gib / 2)),
+        "/tmp" => Some((8 * gib, 8 * gib)),
+        "/var/lib/snapd/snap/core22/1380" => Some((gib / 4, 0)),
+        "/mnt/nas backup" | "/mnt/nas-bind" => Some((1000 * gib, 500 * gib)),
+        _ => None,
+    })
+    .unwrap();
+
+    // Subvolumes and bind mounts of / and the NAS are left out, the ESP
+    // without a size too
+    let mount_points: Vec<&str> = storage
+        .disks
+        .iter()
+        .map(|disk| disk.mount_point.as_str())
+        .collect();
+    assert_eq!(
+        mount_points,
+        [
+            "/",
+            "/boot",
+            "/var/lib/snapd/snap/core22/1380",
+            "/mnt/nas backup"
+        ]
+    );
+    assert!(storage.disks[2].read_only);
+    assert_eq!(storage.total, Some(1401 * gib + gib / 4));
+
+    let mut config = Config::default();
+    config
+        .set("storage_exclude", r#"["/var/lib/snapd/*"]"#)
+        .unwrap();
+    config
+        .set("storage_fs_include", r#"["ext?", "btrfs"]"#)
+        .unwrap();
+    let mut storage = Some(storage);
+    SystemInfo::filter_storage(&mut storage, &config);
+    let storage = storage.unwrap();
+    assert_eq!(storage.disks.len(), 2);
+    assert_eq!(storage.total, Some(401 * gib));
+    assert_eq!(storage.used, Some(300 * gib + gib / 2));
+}
+
+#[test]
+fn modules_sharing_a_field_probe_it_once() {
+    let registry = ModuleRegistry::new();
+    let modules: Vec<_> = ["fans", "kernel", "temperature", "interfaces", "network"]
+        .iter()
+        .map(|name| registry.get(name).unwrap())
+        .collect();
+    let owners: Vec<&str> = field_owners(&modules)
+        .iter()
+        .map(|module| module.name())
+        .collect();
+    assert_eq!(owners, ["fans", "kernel", "interfaces"]);
+
+    // The fans module alone still probes the sensors the temperatures share
+    let info = SystemInfo::new(
+        &fixture("ubuntu"),
+        &modules,
+        &Config::default(),
+        &ProbeCache::disabled(),
+    );
+    assert_eq!(info.sensors.fans.len(), 2);
+    assert_eq!(info.sensors.temperatures.len(), 3);
+}
+
+#[test]
+fn redaction_keeps_only_usage_buckets() {
+    let gib = 1 << 30;
+    let memory = MemoryInfo {
+        used: Some(5 * gib),
+        total: Some(20 * gib),
+        bucket: None,
+    };
+    let redacted = DataRedactor::redact_memory(&memory);
+    assert_eq!(Display::format_memory(&redacted), "25% of 16-32 GB");
+    assert_eq!(
+        serde_json::to_value(&redacted).unwrap(),
+        serde_json::json!({
+            "used": null,
+            "total": null,
+            "bucket": { "percent": 25, "size": "16-32 GB" }
+        })
+    );
+
+    // No swap is nothing to hide
+    let swap = MemoryInfo {
+        used: Some(0),
+        total: Some(0),
+        bucket: None,
+    };
+    assert_eq!(DataRedactor::redact_memory(&swap).total, Some(0));
+
+    let mounts = SystemInfo::parse_mountinfo(
+        &fixture("fedora")
+            .read_to_string("/proc/self/mountinfo")
+            .unwrap(),
+    );
+    let storage = SystemInfo::storage_from_mounts(&mounts, |mount_point| {
+        (mount_point == "/").then_some((400 * gib, 100 * gib))
+    });
+    let storage = DataRedactor::redact_storage(&storage).unwrap();
+    assert_eq!((storage.used, storage.total), (None, None));
+    assert_eq!(storage.disks[0].total, None);
+    assert!(storage
+        .disks
+        .iter()
+        .all(|disk| disk.device.ends_with("XXXX")));
+    assert_eq!(
+        DataRedactor::redact_device("/dev/mapper/luks-0b5a1c3e-7d2f-4e8a-9c61-2f4d8b7e1a90"),
+        "/dev/mapper/XXXX"
+    );
+    assert_eq!(DataRedactor::redact_device("0:45"), "XXXX");
+    assert_eq!(Display::format_storage(&Some(storage)), "75% of 256-512");
+}
@@ -0,0 +1,73 @@
+use crate::terminal::{fit, strip_escapes, visual_width, Overflow};
+
+#[test]
+fn long_lines_are_truncated_with_an_ellipsis() {
+    let line = "\x1b[1;36mCPU\x1b[0m: \x1b[37mIntel(R) Xeon(R) Processor\x1b[0m";
+    assert_eq!(visual_width(line), 31);
+    assert_eq!(fit(line, 40, Overflow::Truncate), [line]);
+
+    let truncated = fit(line, 12, Overflow::Truncate);
+    assert_eq!(truncated, ["\x1b[1;36mCPU\x1b[0m: \x1b[37mIntel(…\x1b[0m"]);
+    assert_eq!(visual_width(&truncated[0]), 12);
+}
+
+#[test]
+fn wrapped_lines_break_at_spaces_and_resume_the_color() {
+    let line = "OS: \x1b[37mDebian GNU/Linux 12 (bookworm)\x1b[0m";
+    let lines = fit(line, 22, Overflow::Wrap);
+    assert_eq!(
+        lines,
+        [
+            "OS: \x1b[37mDebian GNU/Linux\x1b[0m",
+            "  \x1b[37m12 (bookworm)\x1b[0m",
+        ]
+    );
+
+    // Words longer than the line are split
+    let lines = fit("Kernel: 6.18.44-fc-v139", 12, Overflow::Wrap);
+    assert_eq!(lines, ["Kernel:", "  6.18.44-fc", "  -v139"]);
+    assert!(lines.iter().all(|line| visual_width(line) <= 12));
+}
+
+#[test]
+fn widths_follow_east_asian_width_and_graphemes() {
+    assert_eq!(visual_width("東京-server"), 11);
+    assert_eq!(visual_width("🔒 Screenshot mode"), 18);
+    assert_eq!(visual_width("cafe\u{301}"), 4);
+    assert_eq!(visual_width("❤\u{fe0f}"), 2);
+    assert_eq!(visual_width("👩\u{200d}💻"), 2);
+    assert_eq!(visual_width("╭─╮"), 3);
+
+    // A wide character that does not fit is left out whole
+    assert_eq!(fit("ホスト名です", 6, Overflow::Truncate), ["ホス…"]);
+    assert_eq!(fit("ホスト名です", 6, Overflow::Wrap), ["ホスト", "名です"]);
+}
+
+#[test]
+fn osc_hyperlinks_take_no_space_and_survive_cuts() {
+    let link = "\x1b]8;;https://example.com\x1b\\example.com\x1b]8;;\x1b\\";
+    assert_eq!(visual_width(link), 11);
+    assert_eq!(strip_escapes(link), "example.com");
+    assert_eq!(visual_width("\x1b]0;title\x07text\x1b(B"), 4);
+
+    assert_eq!(
+        fit(link, 8, Overflow::Truncate),
+        ["\x1b]8;;https://example.com\x1b\\example…\x1b]8;;\x1b\\"]
+    );
+    assert_eq!(
+        fit(&format!("Site: {}", link), 10, Overflow::Wrap),
+        [
+            "Site:",
+            "  \x1b]8;;https://example.com\x1b\\example.\x1b]8;;\x1b\\",
+            "  \x1b]8;;https://example.com\x1b\\com\x1b]8;;\x1b\\",
+        ]
+    );
+}
+
+#[test]
+fn stray_escape_before_multibyte_text_is_skipped_alone() {
+    assert_eq!(strip_escapes("\x1bé"), "é");
+    assert_eq!(visual_width("\x1bécran"), 5);
+    assert_eq!(strip_escapes("a\x1b(é"), "a(é");
+    assert_eq!(fit("\x1bécran", 3, Overflow::Truncate), ["\x1béc…"]);
+}
@@ -0,0 +1,76 @@
+use super::TempDir;
+use crate::color::ColorChoice;
+use crate::config::Config;
+use crate::migrate::ConfigMigrator;
+use crate::theme::Theme;
+use std::fs;
+use std::path::Path;
+
+#[test]
+fn builtin_presets_load() {
+    let missing = Path::new("/nonexistent");
+    for name in Theme::names(missing) {
+        let theme = Theme::load(&name, missing).unwrap();
+        assert_eq!(theme.palette.len(), 8, "{}", name);
+    }
+
+    let nord = Theme::load("nord", missing).unwrap();
+    assert_eq!(nord.accent, ColorChoice::Rgb(0x81, 0xa1, 0xc1));
+
+    let error = Theme::load("neon", missing).unwrap_err();
+    assert!(error.starts_with("Unknown theme 'neon' (available: default, catppuccin-mocha"));
+}
+
+#[test]
+fn user_themes_and_config_colors_override_presets() {
+    let dir = TempDir::new("theme");
+    fs::write(dir.join("nord.toml"), "accent = \"red\"\n").unwrap();
+    fs::write(dir.join("mine.toml"), "value = 208\n").unwrap();
+
+    // A user file replaces the preset of the same name, missing keys use the
+    // default theme
+    let nord = Theme::load("nord", &dir).unwrap();
+    assert_eq!(nord.accent, ColorChoice::Red);
+    assert_eq!(nord.label, ColorChoice::Cyan);
+    assert_eq!(Theme::names(&dir).last().unwrap(), "mine");
+
+    let mut config = Config::default();
+    config.set("theme", "mine").unwrap();
+    config.set("label_color", "magenta").unwrap();
+    let theme = Theme::for_config(&config, &dir);
+    assert_eq!(theme.value, ColorChoice::Ansi256(208));
+    assert_eq!(theme.label, ColorChoice::Magenta);
+    assert_eq!(theme.accent, ColorChoice::Blue);
+}
+
+#[test]
+fn v2_default_colors_are_dropped_so_themes_apply() {
+    let contents = "version = 2\ntheme = \"nord\"\nlogo_color = \"Cyan\"\naccent_color = \"Blue\"\nlabel_color = \"cyan\"\nvalue_color = \"white\"\nseparator_color = \"blue\"\n";
+    let migration = ConfigMigrator::migrate(contents).unwrap();
+    assert_eq!(migration.from, 2);
+
+    let (config, _) = Config::parse(&migration.migrated).unwrap();
+    assert_eq!(config.logo_color, None);
+    assert_eq!(config.accent_color, None);
+    assert_eq!(config.separator_color, None);
+    assert_eq!(config.theme.as_deref(), Some("nord"));
+}
+
+#[test]
+fn colors_set_on_purpose_survive_migration() {
+    // A profile that only changes some colors is not a generated config
+    let migration = ConfigMigrator::migrate("label_color = \"cyan\"\n").unwrap();
+    assert!(!migration.keys_changed);
+    assert_eq!(migration.migrated, "version = 3\nlabel_color = \"cyan\"\n");
+
+    let contents = "version = 2\nlogo_color = \"cyan\"\naccent_color = \"blue\"\nlabel_color = \"cyan\"\nvalue_color = \"red\"\nseparator_color = \"blue\"\n";
+    let migration = ConfigMigrator::migrate(contents).unwrap();
+    assert_eq!((migration.from, migration.to), (2, 3));
+    assert!(!migration.keys_changed);
+    assert_eq!(
+        migration.migrated,
+        contents.replacen("version = 2", "version = 3", 1)
+    );
+    assert!(ConfigMigrator::migrate(&migration.migrated).is_none());
+    assert_eq!(ConfigMigrator::read_version(&migration.migrated), Some(3));
+}
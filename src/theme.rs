@@ -0,0 +1,131 @@
+use crate::color::ColorChoice;
+use crate::config::Config;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+/// Presets shipped with hxfetch, in the same format as user theme files
+const BUILTIN_THEMES: &[(&str, &str)] = &[
+    (
+        "catppuccin-mocha",
+        include_str!("../themes/catppuccin-mocha.toml"),
+    ),
+    ("nord", include_str!("../themes/nord.toml")),
+    ("gruvbox", include_str!("../themes/gruvbox.toml")),
+    ("dracula", include_str!("../themes/dracula.toml")),
+    ("solarized", include_str!("../themes/solarized.toml")),
+];
+
+/// The colors used for the output. Missing keys in a theme file keep the
+/// colors of the default theme.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Theme {
+    pub logo: ColorChoice,
+    pub accent: ColorChoice,
+    pub label: ColorChoice,
+    pub value: ColorChoice,
+    pub separator: ColorChoice,
+    /// Blocks drawn by the `colors` module
+    pub palette: Vec<ColorChoice>,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            logo: ColorChoice::Cyan,
+            accent: ColorChoice::Blue,
+            label: ColorChoice::Cyan,
+            value: ColorChoice::White,
+            separator: ColorChoice::Blue,
+            palette: vec![
+                ColorChoice::Black,
+                ColorChoice::Red,
+                ColorChoice::Green,
+                ColorChoice::Yellow,
+                ColorChoice::Blue,
+                ColorChoice::Magenta,
+                ColorChoice::Cyan,
+                ColorChoice::White,
+            ],
+        }
+    }
+}
+
+impl Theme {
+    /// The theme selected in `config`, with the config's own `*_color` keys
+    /// applied on top. Theme files are looked up in `themes_dir` first, so
+    /// they can also replace a built-in preset. An unknown theme is reported
+    /// and the default theme is used.
+    pub fn for_config(config: &Config, themes_dir: &Path) -> Self {
+        let mut theme = match &config.theme {
+            Some(name) => Self::load(name, themes_dir).unwrap_or_else(|e| {
+                eprintln!("Warning: {}", e);
+                Self::default()
+            }),
+            None => Self::default(),
+        };
+
+        let overrides = [
+            (&mut theme.logo, config.logo_color),
+            (&mut theme.accent, config.accent_color),
+            (&mut theme.label, config.label_color),
+            (&mut theme.value, config.value_color),
+            (&mut theme.separator, config.separator_color),
+        ];
+        for (slot, color) in overrides {
+            if let Some(color) = color {
+                *slot = color;
+            }
+        }
+
+        theme
+    }
+
+    pub fn load(name: &str, themes_dir: &Path) -> Result<Self, String> {
+        let path = themes_dir.join(format!("{}.toml", name));
+        if let Ok(contents) = fs::read_to_string(&path) {
+            return toml::from_str(&contents)
+                .map_err(|e| format!("{}: {}", path.display(), e.message().trim()));
+        }
+
+        if name == "default" {
+            return Ok(Self::default());
+        }
+
+        let (_, contents) = BUILTIN_THEMES
+            .iter()
+            .find(|(builtin, _)| *builtin == name)
+            .ok_or_else(|| {
+                format!(
+                    "Unknown theme '{}' (available: {})",
+                    name,
+                    Self::names(themes_dir).join(", ")
+                )
+            })?;
+
+        toml::from_str(contents).map_err(|e| e.to_string())
+    }
+
+    /// Built-in presets followed by the user's theme files
+    pub fn names(themes_dir: &Path) -> Vec<String> {
+        let mut names: Vec<String> = std::iter::once("default")
+            .chain(BUILTIN_THEMES.iter().map(|(name, _)| *name))
+            .map(str::to_string)
+            .collect();
+
+        if let Ok(entries) = fs::read_dir(themes_dir) {
+            let mut user: Vec<String> = entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.path())
+                .filter(|path| path.extension().is_some_and(|ext| ext == "toml"))
+                .filter_map(|path| Some(path.file_stem()?.to_str()?.to_string()))
+                .filter(|name| !names.contains(name))
+                .collect();
+            user.sort();
+            names.extend(user);
+        }
+
+        names
+    }
+}